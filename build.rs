@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    let git_dir = Path::new(".git");
+    if git_dir.join("HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        if let Ok(head) = std::fs::read_to_string(git_dir.join("HEAD")) {
+            if let Some(reference) = head.trim().strip_prefix("ref: ") {
+                if git_dir.join(reference).exists() {
+                    println!("cargo:rerun-if-changed=.git/{}", reference);
+                }
+            }
+        }
+        if git_dir.join("packed-refs").exists() {
+            println!("cargo:rerun-if-changed=.git/packed-refs");
+        }
+    }
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=BUILD_DATE={}", date);
+}
@@ -0,0 +1,733 @@
+// command-line entry point: argument parsing, logging setup and the full run the binary performs;
+// library users call query_addresses instead and keep control of logging and the process
+
+use crate::*;
+use clap::{Parser, Subcommand};
+use dotenv::dotenv;
+use std::io::IsTerminal;
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, fmt, EnvFilter};
+
+#[derive(Parser)]
+#[command(name = "evm_tx_checker", about = "EVM 区块链钱包交易查询工具", version = VERSION)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// 钱包文件路径（csv/json/yaml/txt/xlsx），可用逗号分隔多个文件或使用通配符，`-` 表示从标准输入读取；未指定时依次查找 data/wallets.{csv,json,yaml,txt}
+    #[arg(value_name = "INPUT")]
+    input: Option<PathBuf>,
+
+    /// 忽略并覆盖已有的断点文件，从头开始查询
+    #[arg(long)]
+    fresh: bool,
+
+    /// 读取上次的输出文件（xlsx/json/csv），只重新查询其中失败（超时/网络错误/解析失败等）的条目
+    #[arg(long, value_name = "PREV_OUTPUT")]
+    retry_failed: Option<PathBuf>,
+
+    /// 以便于阅读的格式输出日志（默认）
+    #[arg(long, conflicts_with = "log_json")]
+    pretty: bool,
+
+    /// 以 JSON Lines 格式输出日志，便于采集；日志级别由 RUST_LOG 控制
+    #[arg(long)]
+    log_json: bool,
+
+    /// 终端输出是否带颜色；auto 时遵循 NO_COLOR，且输出不是终端时不带颜色
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// 用 [OK]/[WARN]/[ERROR] 等 ASCII 标记代替 ✓/⚠/✗ 等符号
+    #[arg(long)]
+    ascii: bool,
+
+    /// 输出语言（日志和 xlsx 表头）；未指定时读取 OUTPUT_LANG=zh|en，默认中文
+    #[arg(long, value_enum)]
+    lang: Option<Lang>,
+
+    /// 要查询的链，逗号分隔，覆盖 TARGET_CHAINS；`all` 表示所有支持的链，`-bsc` 表示排除某条链
+    #[arg(long, value_name = "CHAINS")]
+    chains: Option<String>,
+
+    /// 只查询前 N 个地址（去重后、跳过 --offset 之后），便于先用少量钱包验证配置；也可用 LIMIT 设置
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// 跳过前 M 个地址（去重后）；也可用 OFFSET 设置
+    #[arg(long, value_name = "M")]
+    offset: Option<usize>,
+
+    /// 与上次的输出文件（json/jsonl/csv/xlsx）按地址和链比较，把变化（变为活跃、有新交易等）写入「变化」工作表
+    #[arg(long, value_name = "PREV_OUTPUT")]
+    diff: Option<PathBuf>,
+
+    /// 跳过启动时的 API 连通性检查（默认先用一个请求确认 API Key 和网络可用）；也可用 PREFLIGHT=0 设置
+    #[arg(long)]
+    no_preflight: bool,
+
+    /// 安静模式：不打印每个地址的查询结果和重试信息，只打印启动配置、运行统计和输出文件路径；也可用 QUIET=1 设置
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// 设置了 USE_CACHE=1 时也不读取结果缓存，重新查询并覆盖缓存
+    #[arg(long, visible_alias = "refresh")]
+    no_cache: bool,
+
+    /// 只加载地址、解析配置并打印查询计划（链、预计请求数、输出文件），不发起任何网络请求
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// 仅从钱包文件推导地址（私钥自动转换），不发起任何 RPC 查询
+    Resolve {
+        /// 输出文件路径，未指定时写到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 检查钱包文件中每一行的格式，存在无法识别的行时以非零状态退出
+    Validate,
+}
+
+enum LogRotation {
+    Never,
+    Daily,
+}
+
+fn load_log_rotation() -> LogRotation {
+    match std::env::var("LOG_ROTATION").unwrap_or_default().to_lowercase().as_str() {
+        "daily" => LogRotation::Daily,
+        _ => LogRotation::Never,
+    }
+}
+
+fn use_color(choice: ColorChoice, to_stderr: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+            let is_terminal = if to_stderr { io::stderr().is_terminal() } else { io::stdout().is_terminal() };
+            !no_color && is_terminal
+        }
+    }
+}
+
+struct ConsoleFormat {
+    ansi: bool,
+}
+
+impl<S, N> FormatEvent<S, N> for ConsoleFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                write!(writer, "{}", span.name())?;
+                if let Some(fields) = span.extensions().get::<FormattedFields<N>>().filter(|fields| !fields.is_empty()) {
+                    write!(writer, "{{{}}}", fields)?;
+                }
+                write!(writer, ": ")?;
+            }
+        }
+
+        let mut message = String::new();
+        ctx.format_fields(Writer::new(&mut message), event)?;
+        let message = console_text(&message);
+        let color = match *event.metadata().level() {
+            Level::ERROR => Some("31"),
+            Level::WARN => Some("33"),
+            Level::INFO if message.starts_with('✓') || message.starts_with("[OK]") => Some("32"),
+            Level::DEBUG | Level::TRACE => Some("2"),
+            _ => None,
+        };
+        match color {
+            Some(code) if self.ansi => writeln!(writer, "\x1b[{}m{}\x1b[0m", code, message),
+            _ => writeln!(writer, "{}", message),
+        }
+    }
+}
+
+fn init_logging(cli: &Cli) -> Result<Option<WorkerGuard>> {
+    let (json, to_stderr) = (cli.log_json, cli.command.is_some());
+    let ansi = use_color(cli.color, to_stderr);
+    let _ = ASCII_OUTPUT.set(cli.ascii || std::env::var("ASCII_OUTPUT").is_ok_and(|v| v == "1"));
+    let console_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let quiet = cli.quiet || std::env::var("QUIET").is_ok_and(|v| v == "1");
+    let unmuted = filter::dynamic_filter_fn(move |_, _| !quiet || !CONSOLE_MUTED.load(Ordering::Relaxed));
+    let writer = if to_stderr { BoxMakeWriter::new(io::stderr) } else { BoxMakeWriter::new(io::stdout) };
+    let console = if json {
+        fmt::layer().json().with_writer(writer).boxed()
+    } else {
+        fmt::layer().event_format(ConsoleFormat { ansi }).with_ansi(ansi).with_writer(writer).boxed()
+    };
+
+    let log_file = std::env::var("LOG_FILE").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let (file, guard) = match log_file {
+        Some(log_file) => {
+            let path = Path::new(&log_file);
+            let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let name = path.file_name().ok_or_else(|| anyhow::anyhow!(tr!("config.invalid_log_file", log_file)))?;
+            std::fs::create_dir_all(dir)?;
+            let appender = match load_log_rotation() {
+                LogRotation::Daily => tracing_appender::rolling::daily(dir, name),
+                LogRotation::Never => tracing_appender::rolling::never(dir, name),
+            };
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let file_filter = EnvFilter::try_from_env("LOG_FILE_LEVEL").unwrap_or_else(|_| EnvFilter::new("info,evm_tx_checker=debug"));
+            let layer = if json {
+                fmt::layer().json().with_writer(writer).boxed()
+            } else {
+                fmt::layer().with_ansi(false).with_writer(writer).boxed()
+            };
+            (Some(layer.with_filter(file_filter)), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry().with(console.with_filter(console_filter).with_filter(unmuted)).with(file).init();
+    Ok(guard)
+}
+
+// OUTPUT_LANG rather than the shell's LANG, which dotenv never overrides; locale forms such as en_US.UTF-8 count by their language part
+fn load_lang(cli_lang: Option<Lang>) -> Lang {
+    cli_lang.unwrap_or_else(|| {
+        let value = std::env::var("OUTPUT_LANG").unwrap_or_default().trim().to_lowercase();
+        match value.split(['_', '-', '.']).next().unwrap_or_default() {
+            "en" => Lang::En,
+            _ => Lang::Zh,
+        }
+    })
+}
+
+struct ManifestInput<'a> {
+    cli: &'a Cli,
+    config: &'a QueryConfig,
+    output_formats: &'a [OutputFormat],
+    chains: &'a [String],
+    input_hash: &'a str,
+    started: DateTime<Local>,
+    timed_out: bool,
+}
+
+// the settings that shape a run's results; secrets are never written, only whether they were set
+fn run_manifest(input: &ManifestInput, report: &notify::RunReport) -> serde_json::Value {
+    let ManifestInput { cli, config, .. } = input;
+    serde_json::json!({
+        "tool_version": VERSION,
+        "started_at": input.started.to_rfc3339(),
+        "finished_at": Local::now().to_rfc3339(),
+        "interrupted": report.interrupted,
+        "timed_out": input.timed_out,
+        "input": {
+            "path": cli.input.as_ref().map(|p| p.display().to_string()),
+            "retry_failed": cli.retry_failed.as_ref().map(|p| p.display().to_string()),
+            "diff": cli.diff.as_ref().map(|p| p.display().to_string()),
+            "hash": input.input_hash,
+        },
+        "chains": input.chains,
+        "config": {
+            "provider": config.provider.name(),
+            "secondary_provider": config.secondary.as_ref().map(SecondaryProvider::name),
+            "api_key": if config.api_key.is_empty() { "" } else { "<redacted>" },
+            "query_mode": config.query_mode,
+            "custom_method": config.custom_method.as_ref().map(|c| serde_json::json!({ "method": c.method, "result_path": c.result_path })),
+            "concurrency": config.concurrency,
+            "adaptive_bounds": config.adaptive_bounds,
+            "quota_low_remaining": config.quota_low_remaining,
+            "chain_concurrency": config.chain_concurrency,
+            "rate_limit_rps": config.rate_limit_rps,
+            "proxies": config.proxy_urls.iter().map(|url| mask_proxy_url(url)).collect::<Vec<_>>(),
+            "rpc_batch_size": load_rpc_batch_size(),
+            "skip_confirm": config.skip_confirm,
+            "empty_recheck_secs": config.confirm_empty_delay.map(|delay| delay.as_secs()),
+            "query_span": config.query_span,
+            "batch_size": config.batch_size,
+            "batch_delay_secs": config.batch_delay.as_secs(),
+            "max_runtime_secs": load_max_runtime().map(|limit| limit.as_secs()),
+            "output_formats": input.output_formats.iter().map(|f| f.name()).collect::<Vec<_>>(),
+        },
+        "summary": report,
+    })
+}
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let started_at = Instant::now();
+    let started_wall = Local::now();
+    dotenv().ok();
+    i18n::set_lang(load_lang(cli.lang));
+    let _log_guard = init_logging(&cli)?;
+
+    match cli.command {
+        Some(Command::Resolve { output }) => return resolve_addresses(cli.input.as_deref(), output.as_deref(), cli.chains.as_deref()),
+        Some(Command::Validate) => return validate_wallet_file(cli.input.as_deref(), cli.chains.as_deref()),
+        None => {}
+    }
+
+    let time_settings = load_time_settings()?;
+    let explorers = load_explorers()?;
+    let history_plan = load_history_plan()?.map(Arc::new);
+    let network = load_network()?;
+    let mut config = QueryConfig { provider: load_provider()?, custom_method: load_custom_method()?, secondary: load_secondary_provider()?, ..QueryConfig::from_env()? };
+    if cli.chains.is_some() {
+        config.chains = load_target_chains(cli.chains.as_deref());
+    }
+    let mock = matches!(config.provider, Provider::Mock(_));
+    let clients = ClientPool::new(&config.proxy_urls, &config.client_settings)?;
+    let QueryConfig { api_key, concurrency, adaptive_bounds, chain_concurrency, rate_limit_rps, query_mode, proxy_urls, client_settings, .. } = &config;
+    let output_layout = load_output_layout();
+    let output_mode = load_output_mode();
+    let output_formats = load_output_formats();
+    let gsheets_target = if output_formats.contains(&OutputFormat::GSheets) { Some(gsheets::load_target()?) } else { None };
+    let sort_order = load_sort_order();
+    let inactive_days = load_inactive_days();
+    let inactive_only = std::env::var("INACTIVE_ONLY").map(|v| v == "1").unwrap_or(false);
+    let result_filter = load_result_filter();
+    let optional_columns = load_optional_columns();
+    let webhook = notify::load_webhook();
+    let telegram = notify::load_telegram();
+    let max_runtime = load_max_runtime();
+    let exclude_contracts = std::env::var("EXCLUDE_CONTRACTS").map(|v| v == "1").unwrap_or(false);
+    let classify_accounts_enabled = exclude_contracts || std::env::var("CLASSIFY_ACCOUNTS").map(|v| v == "1").unwrap_or(false);
+    let time_settings = TIME_SETTINGS.get_or_init(|| time_settings);
+    EXPLORERS.get_or_init(|| explorers);
+    let placeholders = PLACEHOLDERS.get_or_init(Placeholders::from_env);
+    if placeholders.hash.is_some() || placeholders.time.is_some() {
+        let hash = placeholders.hash.as_ref().map(|text| format!("\"{}\"", text)).unwrap_or_else(|| t("config.placeholder_status").to_string());
+        let time = format!("\"{}\"", placeholders.time.as_deref().unwrap_or(NO_TX_TIME));
+        info!("{}", tr!("config.placeholders", hash, time));
+    }
+
+    if api_key.is_empty() && !mock {
+        warn!("{}", t("config.missing_api_key"));
+        warn!("{}", t("config.missing_api_key_env"));
+        warn!("{}", t("config.missing_api_key_var"));
+        warn!("{}", tr!("config.api_key_format"));
+    } else if !api_key.is_empty() {
+        info!("{}", tr!("config.api_key_loaded", api_key.chars().take(8).collect::<String>()));
+    }
+
+    match proxy_urls.as_slice() {
+        [] => {}
+        [url] => info!("{}", tr!("config.proxy", mask_proxy_url(url))),
+        urls => info!("{}", tr!("config.proxy_pool", urls.len())),
+    }
+    if let Some(user_agent) = &client_settings.user_agent {
+        info!("{}", tr!("config.user_agent", user_agent));
+    }
+    if client_settings.http2_only {
+        info!("{}", t("config.http2_only"));
+    }
+    info!("{}", tr!("config.concurrency", concurrency));
+    if let Some((min, max)) = adaptive_bounds {
+        info!("{}", tr!("config.adaptive", min, max));
+    }
+    if !chain_concurrency.is_empty() {
+        let mut limits: Vec<String> = chain_concurrency.iter().map(|(chain, limit)| format!("{}={}", chain, limit)).collect();
+        limits.sort();
+        info!("{}", tr!("config.chain_concurrency", limits.join(", ")));
+    }
+    if let Some(rps) = rate_limit_rps {
+        info!("{}", tr!("config.rate_limit", rps));
+    }
+    info!("{}", tr!("config.query_mode", query_mode));
+    if let Some(custom) = &config.custom_method {
+        info!("{}", tr!("config.custom_method", custom.method, custom.result_path));
+    }
+    if config.skip_confirm {
+        info!("{}", t("config.skip_confirm"));
+    }
+    if config.batch_delay > Duration::ZERO {
+        info!("{}", tr!("config.batches", config.batch_size, config.batch_delay.as_secs()));
+    }
+    if let Some(delay) = config.confirm_empty_delay {
+        info!("{}", tr!("config.confirm_empty", delay.as_secs()));
+    }
+    if config.query_span {
+        info!("{}", t("config.query_span"));
+    }
+    if network == Network::Testnet {
+        info!("{}", t("config.testnet"));
+    }
+    info!("{}", tr!("config.time_format", time_settings.format, time_settings.zone.label()));
+
+    let diff_base = match &cli.diff {
+        Some(path) => {
+            let rows = load_previous_results(path)?;
+            info!("{}", tr!("diff.loaded", path.display(), rows.len()));
+            Some(rows)
+        }
+        None => None,
+    };
+
+    let (wallets, target_chains, previous) = match &cli.retry_failed {
+        Some(path) => {
+            let previous = load_previous_results(path)?;
+            let failed = previous.iter().filter(|r| r.status.is_error()).count();
+            info!("{}", tr!("retry.loaded", path.display(), previous.len(), failed));
+            let (wallets, chains) = previous_inputs(&previous);
+            let ok: Vec<QueryResult> = previous.into_iter().filter(|r| !r.status.is_error()).collect();
+            (wallets, chains, ok)
+        }
+        None => {
+            let wallets = load_wallet_addresses(cli.input.as_deref(), &config.chains)?;
+            let wallets = apply_limit(wallets, load_offset(cli.offset), load_limit(cli.limit));
+            (wallets, apply_network(config.chains.clone(), network, cli.chains.as_deref())?, Vec::new())
+        }
+    };
+    validate_target_chains(&target_chains)?;
+    info!("{}", tr!("config.chains", target_chains.join(", ")));
+    let addresses_str: Vec<String> = wallets.iter().map(|w| w.address.clone()).collect();
+    let labels: std::collections::HashMap<&str, &str> =
+        wallets.iter().filter_map(|w| w.label.as_deref().map(|label| (w.address.as_str(), label))).collect();
+
+    if cli.dry_run {
+        let resumed = if cli.fresh { Vec::new() } else { Checkpoint::load(&input_hash(&addresses_str, &target_chains)) };
+        print_dry_run_plan(&addresses_str, &target_chains, &previous, &resumed, query_mode, classify_accounts_enabled, config.confirm_empty_delay.is_some());
+        return Ok(());
+    }
+
+    let run_hash = input_hash(&addresses_str, &target_chains);
+    // the cache only stands in for a plain run; --retry-failed always queries the failed entries again
+    let use_cache = load_use_cache() && cli.retry_failed.is_none();
+    let cache_key = results_cache_key(&addresses_str, &target_chains, &config);
+    let cached = if use_cache && !cli.no_cache { load_results_cache(&cache_key) } else { None };
+    let (checkpoint, resumed) = match &cached {
+        Some(cache) => {
+            info!("{}", tr!("cache.hit", RESULTS_CACHE_FILE, cache.created, cache.results.len()));
+            (None, cache.results.clone())
+        }
+        None => {
+            let (mut checkpoint, resumed) = Checkpoint::open(&run_hash, cli.fresh)?;
+            if !resumed.is_empty() {
+                info!("{}", tr!("checkpoint.resumed", CHECKPOINT_FILE, resumed.len()));
+            }
+            checkpoint.mark_completed(&previous);
+            (Some(checkpoint), merge_results(previous, resumed, &addresses_str))
+        }
+    };
+    let ctx = Arc::new(QueryContext::new(clients, &config, checkpoint));
+
+    let (solana_addresses, mut evm_addresses): (Vec<String>, Vec<String>) = match &cached {
+        Some(_) => (Vec::new(), Vec::new()),
+        None => addresses_str.iter().cloned().partition(|addr| is_solana_address(addr)),
+    };
+    let evm_chains: Vec<String> = target_chains.iter().filter(|c| *c != SOLANA_CHAIN).cloned().collect();
+
+    let preflight_enabled = !cli.no_preflight && load_preflight() && !mock;
+    if let Some(chain) = evm_chains.first().filter(|_| preflight_enabled && !evm_addresses.is_empty()) {
+        let started = Instant::now();
+        match preflight(&ctx, chain).await {
+            Ok(()) => info!("{}", tr!("preflight.ok", chain_display_name(chain), started.elapsed().as_millis())),
+            Err(e) => anyhow::bail!(tr!("preflight.failed", chain_display_name(chain), e)),
+        }
+    }
+    let check_secondary = preflight_enabled && matches!(query_mode.as_str(), "single" | "multi") && !evm_addresses.is_empty();
+    let secondary_chain = evm_chains.iter().find(|chain| etherscan::chain_id(chain).is_some()).filter(|_| check_secondary);
+    if let (Some(secondary), Some(chain)) = (&config.secondary, secondary_chain) {
+        match secondary_preflight(&ctx, secondary, chain).await {
+            Ok(()) => info!("{}", tr!("secondary.preflight_ok", secondary.name())),
+            Err(e) => anyhow::bail!(tr!("secondary.preflight_failed", secondary.name(), e)),
+        }
+    }
+
+    let account_types = match evm_chains.first() {
+        _ if cached.is_some() => resumed.iter().filter_map(|row| row.account_type.map(|t| (row.address.clone(), t))).collect(),
+        Some(chain) if classify_accounts_enabled => {
+            info!("{}", tr!("account.classifying", chain_display_name(chain), evm_addresses.len()));
+            let account_types = classify_accounts(ctx.clone(), &evm_addresses, chain).await;
+            let contracts = account_types.values().filter(|t| **t == AccountType::Contract).count();
+            info!("{}", tr!("account.summary", account_types.len() - contracts, contracts));
+            account_types
+        }
+        _ => std::collections::HashMap::new(),
+    };
+    if exclude_contracts {
+        evm_addresses.retain(|addr| account_types.get(addr) != Some(&AccountType::Contract));
+    }
+    let column_data = ColumnData { label: !labels.is_empty(), account_type: !account_types.is_empty(), dormant: inactive_days.is_some(), span: config.query_span, token: query_mode == "tokens", result: query_mode == "custom", provider: config.secondary.is_some() && matches!(query_mode.as_str(), "single" | "multi") };
+    optional_columns.warn_missing(column_data);
+
+    if output_formats.iter().any(|f| f.is_streaming()) {
+        let create = |format: OutputFormat, path: &str| -> Result<Option<File>> {
+            Ok(if output_formats.contains(&format) { Some(File::create(path)?) } else { None })
+        };
+        let mut stream = StreamOutput {
+            csv: create(OutputFormat::Csv, STREAM_CSV_FILE)?.map(csv::Writer::from_writer),
+            jsonl: create(OutputFormat::Jsonl, STREAM_JSONL_FILE)?.map(io::BufWriter::new),
+            columns: optional_columns.csv(column_data),
+            inactive_days,
+            labels: labels.iter().map(|(addr, label)| (addr.to_string(), label.to_string())).collect(),
+            account_types: account_types.clone(),
+            exclude_contracts,
+            inactive_only,
+            filter: result_filter,
+            tally: RunTally::default(),
+        };
+        if let Some(writer) = &mut stream.csv {
+            writer.write_record(csv_header(&stream.columns))?;
+        }
+        stream.write(&resumed);
+        *ctx.stream.lock().unwrap() = Some(stream);
+        ctx.keep_results.store(diff_base.is_some() || !output_formats.iter().all(|f| f.is_streaming()), Ordering::SeqCst);
+    }
+
+    CONSOLE_MUTED.store(true, Ordering::Relaxed);
+    let query = run_queries(ctx.clone(), &evm_addresses, &solana_addresses, &target_chains, query_mode);
+    tokio::pin!(query);
+    let deadline = async {
+        match max_runtime {
+            Some(limit) => tokio::time::sleep(limit.saturating_sub(started_at.elapsed())).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let mut timed_out = false;
+    let fresh_results = tokio::select! {
+        results = &mut query => results,
+        _ = deadline => {
+            CONSOLE_MUTED.store(false, Ordering::Relaxed);
+            warn!("{}", tr!("run.deadline", max_runtime.unwrap_or_default().as_secs(), SHUTDOWN_GRACE_SECS));
+            timed_out = true;
+            ctx.shutting_down.store(true, Ordering::SeqCst);
+            match timeout(Duration::from_secs(SHUTDOWN_GRACE_SECS), &mut query).await {
+                Ok(results) => results,
+                Err(_) => ctx.take_completed(),
+            }
+        }
+        _ = tokio::signal::ctrl_c() => {
+            CONSOLE_MUTED.store(false, Ordering::Relaxed);
+            warn!("{}", tr!("run.ctrl_c", SHUTDOWN_GRACE_SECS));
+            ctx.shutting_down.store(true, Ordering::SeqCst);
+            tokio::spawn(async {
+                let _ = tokio::signal::ctrl_c().await;
+                error!("{}", t("run.force_exit"));
+                std::process::exit(130);
+            });
+            match timeout(Duration::from_secs(SHUTDOWN_GRACE_SECS), &mut query).await {
+                Ok(results) => results,
+                Err(_) => ctx.take_completed(),
+            }
+        }
+    };
+    let interrupted = ctx.is_shutting_down();
+    let activity = match &history_plan {
+        Some(plan) if !interrupted && !evm_chains.is_empty() && !evm_addresses.is_empty() => {
+            info!("{}", tr!("activity.fetching", evm_addresses.len(), plan.max_pages, ACTIVITY_PAGE_SIZE));
+            let rows = collect_activity(ctx.clone(), &evm_addresses, &evm_chains, plan.clone()).await;
+            let truncated = rows.iter().filter(|row| row.truncated).count();
+            if truncated > 0 {
+                warn!("{}", tr!("activity.truncated", truncated, plan.max_pages));
+            }
+            rows
+        }
+        _ => Vec::new(),
+    };
+    let show_activity = history_plan.as_ref().is_some_and(|plan| plan.months) && !activity.is_empty();
+    let contracts = history_plan.as_ref().map(|plan| plan.contracts.as_slice()).filter(|_| !activity.is_empty()).unwrap_or_default();
+    CONSOLE_MUTED.store(false, Ordering::Relaxed);
+    ctx.clients.print_stats();
+    let stream = ctx.stream.lock().unwrap().take();
+    let mut results = merge_results(resumed, fresh_results, &addresses_str);
+    for row in &mut results {
+        if let Some(label) = labels.get(row.address.as_str()) {
+            row.label = Some(label.to_string());
+        }
+        row.account_type = account_types.get(&row.address).copied();
+    }
+    if use_cache && cached.is_none() && !interrupted {
+        write_results_cache(&cache_key, &results);
+    }
+    if exclude_contracts {
+        results.retain(|row| row.account_type != Some(AccountType::Contract));
+    }
+
+    let changes = match &diff_base {
+        Some(previous) => {
+            let mut changes = diff_results(previous, &results);
+            changes.sort_by_key(|change| target_chains.iter().position(|chain| *chain == change.current.tx_chain));
+            let count = |kind: Change| changes.iter().filter(|change| change.change == kind).count();
+            info!(
+                "{}",
+                tr!("diff.summary", changes.len(), count(Change::Activated), count(Change::NewTransaction), count(Change::Deactivated), count(Change::NewAddress))
+            );
+            let current: HashSet<&str> = results.iter().map(|row| row.tx_chain.as_str()).collect();
+            let before: HashSet<&str> = previous.iter().map(|row| row.tx_chain.as_str()).collect();
+            let mut skipped: Vec<&str> = current.symmetric_difference(&before).copied().collect();
+            skipped.sort();
+            if !skipped.is_empty() {
+                warn!("{}", tr!("diff.unmatched_chains", skipped.join(", ")));
+            }
+            Some(changes)
+        }
+        None => None,
+    };
+
+    let mut grouped: std::collections::HashMap<String, Vec<&QueryResult>> = std::collections::HashMap::new();
+    for row in &results {
+        grouped.entry(row.tx_chain.clone()).or_default().push(row);
+    }
+    if sort_order == SortOrder::Time {
+        for rows in grouped.values_mut() {
+            rows.sort_by_key(|row| std::cmp::Reverse(row.tx_epoch));
+        }
+    }
+    let mut unexpected: Vec<String> = grouped.keys().filter(|chain| !target_chains.contains(chain)).cloned().collect();
+    unexpected.sort();
+    if !unexpected.is_empty() {
+        warn!("{}", tr!("output.unexpected_chains", unexpected.join(", ")));
+    }
+    let sheet_chains: Vec<String> = target_chains.iter().cloned().chain(unexpected).collect();
+
+    let mut workbook = Workbook::new();
+    let formats = SheetFormats::new();
+    let filename = OUTPUT_FILE;
+    let kept_sheets = match output_mode {
+        OutputMode::Append if output_formats.contains(&OutputFormat::Xlsx) && Path::new(filename).exists() => copy_existing_sheets(&mut workbook, Path::new(filename), &formats)?,
+        _ => 0,
+    };
+    let latencies = ctx.latencies.lock().unwrap().clone();
+    write_summary_sheet(&mut workbook, &grouped, &sheet_chains, addresses_str.len(), started_at.elapsed(), &latencies)?;
+
+    if let (Some(days), true) = (inactive_days, inactive_only) {
+        for rows in grouped.values_mut() {
+            rows.retain(|row| is_dormant(row, days) == Some(true));
+        }
+    }
+    if let Some(filter) = result_filter {
+        let before: usize = grouped.values().map(Vec::len).sum();
+        for rows in grouped.values_mut() {
+            rows.retain(|row| filter.keeps(row));
+        }
+        let kept: usize = grouped.values().map(Vec::len).sum();
+        let name = if filter == ResultFilter::Active { "active" } else { "inactive" };
+        info!("{}", tr!("output.filtered", name, kept, before - kept));
+    }
+
+    match output_layout {
+        OutputLayout::ByChain => write_chain_sheets(&mut workbook, &grouped, &sheet_chains, inactive_days, &optional_columns, &formats)?,
+        OutputLayout::ByAddress => write_address_sheet(&mut workbook, &grouped, &addresses_str, &sheet_chains, inactive_days, &formats)?,
+        OutputLayout::Flat => write_flat_sheet(&mut workbook, &grouped, &sheet_chains, inactive_days, &optional_columns, &formats)?,
+    }
+    if show_activity {
+        write_activity_sheet(&mut workbook, &activity, &formats)?;
+    }
+    if !contracts.is_empty() {
+        write_contract_sheet(&mut workbook, &activity, contracts, &formats)?;
+    }
+    if let Some(changes) = &changes {
+        write_changes_sheet(&mut workbook, changes, &formats)?;
+    }
+    if results.iter().any(|r| r.status.is_error()) {
+        write_errors_sheet(&mut workbook, &results, &sheet_chains, &formats)?;
+    }
+
+    let mut destinations = Vec::new();
+    let mut saved_workbook = None;
+    if output_formats.contains(&OutputFormat::Xlsx) {
+        if output_mode == OutputMode::Append {
+            date_new_sheets(&mut workbook, kept_sheets)?;
+            // the kept sheets were copied in first; the new summary still opens the workbook
+            workbook.worksheets_mut()[..=kept_sheets].rotate_right(1);
+        }
+        let path = save_workbook(&mut workbook, filename, &results)?;
+        destinations.push(path.clone());
+        saved_workbook = Some(path);
+    }
+    if output_formats.contains(&OutputFormat::CsvSplit) {
+        let dir = load_output_dir();
+        write_chain_csvs(&dir, &grouped, &sheet_chains, inactive_days, &optional_columns)?;
+        destinations.push(format!("{}/", dir.display()));
+    }
+    if let Some(target) = &gsheets_target {
+        let client = build_client(proxy_urls.first().map(String::as_str), client_settings)?;
+        destinations.push(gsheets::write_tabs(&client, target, &grouped, &sheet_chains, inactive_days, &optional_columns).await?);
+    }
+    if output_formats.iter().any(|f| !matches!(f, OutputFormat::Xlsx | OutputFormat::GSheets)) {
+        if show_activity {
+            write_activity_csv(Path::new(ACTIVITY_CSV_FILE), &activity)?;
+            destinations.push(ACTIVITY_CSV_FILE.to_string());
+        }
+        if !contracts.is_empty() {
+            write_contract_csv(Path::new(CONTRACTS_CSV_FILE), &activity, contracts)?;
+            destinations.push(CONTRACTS_CSV_FILE.to_string());
+        }
+        if let Some(changes) = &changes {
+            write_changes_csv(Path::new(CHANGES_CSV_FILE), changes)?;
+            destinations.push(CHANGES_CSV_FILE.to_string());
+        }
+    }
+    if let Some(stream) = &stream {
+        destinations.extend(stream.csv.is_some().then(|| STREAM_CSV_FILE.to_string()));
+        destinations.extend(stream.jsonl.is_some().then(|| STREAM_JSONL_FILE.to_string()));
+    }
+    let destination = destinations.join(", ");
+    let tally = match stream {
+        Some(stream) => stream.tally,
+        None => RunTally::from_results(&results),
+    };
+    print_run_summary(&tally, addresses_str.len(), &ctx, started_at.elapsed());
+    let unqueried = if timed_out { addresses_str.iter().filter(|addr| !tally.addresses.contains_key(*addr)).count() } else { 0 };
+    if timed_out {
+        warn!("{}", tr!("run.deadline_unqueried", unqueried, addresses_str.len()));
+    }
+    let report = run_report(&tally, addresses_str.len(), &ctx, started_at.elapsed(), destinations, interrupted);
+    let manifest_input = ManifestInput {
+        cli: &cli,
+        config: &config,
+        output_formats: &output_formats,
+        chains: &target_chains,
+        input_hash: &run_hash,
+        started: started_wall,
+        timed_out,
+    };
+    write_run_manifest(&run_manifest(&manifest_input, &report));
+    if webhook.is_some() || telegram.is_some() {
+        let client = build_client(proxy_urls.first().map(String::as_str), client_settings)?;
+        if let Some(webhook) = &webhook {
+            notify::send_webhook(&client, webhook, &report).await;
+        }
+        if let Some(telegram) = &telegram {
+            let document = saved_workbook.as_deref().map(Path::new);
+            notify::send_telegram(&client, telegram, &report, document).await;
+        }
+    }
+
+    if interrupted {
+        warn!("{}", tr!("run.interrupted", results.len(), destination, CHECKPOINT_FILE));
+        // a scheduled run cut short by MAX_RUNTIME_SECS must not exit as if it had finished
+        if unqueried > 0 {
+            anyhow::bail!(tr!("run.deadline_failed", unqueried, addresses_str.len()));
+        }
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(CHECKPOINT_FILE);
+
+    info!("{}", tr!("run.done", destination));
+
+    if let Some(threshold) = load_fail_threshold() {
+        let errors = tally.errors();
+        let fraction = if tally.rows == 0 { 0.0 } else { errors as f64 / tally.rows as f64 };
+        if fraction > threshold {
+            anyhow::bail!(tr!("run.error_threshold", errors, tally.rows, format!("{:.1}", fraction * 100.0), format!("{:.1}", threshold * 100.0)));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+// Etherscan API v2 as a secondary provider (SECONDARY_PROVIDER=etherscan): the latest normal transaction of an address
+
+use crate::Transaction;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+
+pub(crate) const ETHERSCAN_API: &str = "https://api.etherscan.io/v2/api";
+
+// chains the v2 API serves, by EVM chain id
+const CHAIN_IDS: [(&str, u64); 17] = [
+    ("eth", 1),
+    ("bsc", 56),
+    ("polygon", 137),
+    ("arbitrum", 42161),
+    ("optimism", 10),
+    ("avalanche", 43114),
+    ("zksync", 324),
+    ("base", 8453),
+    ("gnosis", 100),
+    ("linea", 59144),
+    ("scroll", 534352),
+    ("eth_sepolia", 11155111),
+    ("eth_holesky", 17000),
+    ("polygon_amoy", 80002),
+    ("avalanche_fuji", 43113),
+    ("base_sepolia", 84532),
+    ("optimism_testnet", 11155420),
+];
+
+// why a lookup failed; only transient failures are worth another attempt
+#[derive(Debug)]
+pub(crate) enum EtherscanError {
+    // network errors, timeouts, HTTP 429/5xx and Etherscan's own "Max rate limit reached"
+    Transient(String),
+    // an invalid API key, an unsupported chain or an answer that cannot be read does not change on a retry
+    Permanent(String),
+}
+
+impl EtherscanError {
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(self, EtherscanError::Transient(_))
+    }
+}
+
+impl std::fmt::Display for EtherscanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EtherscanError::Transient(message) | EtherscanError::Permanent(message) => f.write_str(message),
+        }
+    }
+}
+
+pub(crate) fn chain_id(chain: &str) -> Option<u64> {
+    CHAIN_IDS.iter().find(|(id, _)| *id == chain).map(|(_, chain_id)| *chain_id)
+}
+
+// status "0" covers both "No transactions found" (result: []) and errors (result: "Invalid API Key", rate limits, ...)
+#[derive(Deserialize)]
+struct Response {
+    status: String,
+    message: String,
+    result: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EtherscanTx {
+    hash: String,
+    time_stamp: String,
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    value: Option<String>,
+}
+
+impl EtherscanTx {
+    // same shape as an Ankr transaction: hex timestamp and hex wei value
+    fn into_transaction(self, chain: &str) -> Transaction {
+        let hex = |decimal: &str| ethers::types::U256::from_dec_str(decimal.trim()).ok().map(|n| format!("{:#x}", n));
+        Transaction {
+            hash: self.hash,
+            timestamp: hex(&self.time_stamp).unwrap_or_default(),
+            blockchain: chain.to_string(),
+            from: self.from.filter(|a| !a.is_empty()),
+            // contract creations have an empty `to`
+            to: self.to.filter(|a| !a.is_empty()),
+            value: self.value.as_deref().and_then(hex),
+        }
+    }
+}
+
+pub(crate) async fn last_transaction(client: &Client, base_url: &str, api_key: &str, chain: &str, address: &str) -> Result<Option<Transaction>, EtherscanError> {
+    use EtherscanError::{Permanent, Transient};
+    let chain_id = chain_id(chain).ok_or_else(|| Permanent(format!("unsupported chain {}", chain)))?;
+    let query = [
+        ("chainid", chain_id.to_string()),
+        ("module", "account".to_string()),
+        ("action", "txlist".to_string()),
+        ("address", address.to_string()),
+        ("page", "1".to_string()),
+        ("offset", "1".to_string()),
+        ("sort", "desc".to_string()),
+        ("apikey", api_key.to_string()),
+    ];
+    let response = client.get(base_url).query(&query).send().await.map_err(|e| Transient(e.without_url().to_string()))?;
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        return Err(Transient(format!("HTTP {}", status.as_u16())));
+    }
+    if !status.is_success() {
+        return Err(Permanent(format!("HTTP {}", status.as_u16())));
+    }
+    let body: Response = response.json().await.map_err(|e| Permanent(e.without_url().to_string()))?;
+    match body.result {
+        Value::Array(txs) if body.status == "1" || txs.is_empty() => match txs.into_iter().next() {
+            Some(tx) => serde_json::from_value::<EtherscanTx>(tx).map(|tx| Some(tx.into_transaction(chain))).map_err(|e| Permanent(e.to_string())),
+            None => Ok(None),
+        },
+        // "Max rate limit reached" and "Max calls per sec rate limit reached (5/sec)"
+        Value::String(error) if error.to_lowercase().contains("rate limit") => Err(Transient(format!("{}: {}", body.message, error))),
+        Value::String(error) => Err(Permanent(format!("{}: {}", body.message, error))),
+        _ => Err(Permanent(body.message)),
+    }
+}
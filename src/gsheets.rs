@@ -0,0 +1,172 @@
+// Google Sheets output (OUTPUT_FORMAT=gsheets): one tab per chain, mirroring the xlsx chain sheets
+
+use crate::i18n::{t, tr};
+use crate::{chain_display_name, sheet_name, Column, ColumnData, OptionalColumns, QueryResult, MAX_RETRIES};
+use anyhow::Result;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+const SHEETS_API: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+// the API rejects very large request bodies, so rows are written in chunks
+const DEFAULT_CHUNK_ROWS: usize = 5000;
+const THROTTLE_DELAY_SECS: u64 = 10;
+
+#[derive(Deserialize)]
+struct ServiceAccount {
+    client_email: String,
+    private_key: String,
+    #[serde(default)]
+    token_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+pub(crate) struct Target {
+    spreadsheet_id: String,
+    client_email: String,
+    token_uri: String,
+    key: EncodingKey,
+    chunk_rows: usize,
+}
+
+// read and check everything up front so a bad credential fails before the queries run, not after
+pub(crate) fn load_target() -> Result<Target> {
+    let read = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let spreadsheet_id = read("GSHEETS_SPREADSHEET_ID").ok_or_else(|| anyhow::anyhow!(t("gsheets.missing_spreadsheet")))?;
+    let path = read("GOOGLE_APPLICATION_CREDENTIALS").ok_or_else(|| anyhow::anyhow!(t("gsheets.missing_credentials")))?;
+    let text = std::fs::read_to_string(&path).map_err(|e| anyhow::anyhow!(tr!("gsheets.credentials_read_failed", path, e)))?;
+    let account: ServiceAccount = serde_json::from_str(&text).map_err(|e| anyhow::anyhow!(tr!("gsheets.credentials_parse_failed", path, e)))?;
+    let key = EncodingKey::from_rsa_pem(account.private_key.as_bytes()).map_err(|e| anyhow::anyhow!(tr!("gsheets.credentials_parse_failed", path, e)))?;
+    let chunk_rows = read("GSHEETS_CHUNK_ROWS").and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_CHUNK_ROWS).max(1);
+
+    info!("{}", tr!("gsheets.target", spreadsheet_id, account.client_email));
+    Ok(Target {
+        spreadsheet_id,
+        client_email: account.client_email,
+        token_uri: account.token_uri.unwrap_or_else(|| DEFAULT_TOKEN_URI.to_string()),
+        key,
+        chunk_rows,
+    })
+}
+
+fn error_message(text: &str) -> String {
+    let body: Value = serde_json::from_str(text).unwrap_or(Value::Null);
+    let message = [body.pointer("/error/message"), body.get("error_description"), body.get("error")].into_iter().flatten().find_map(Value::as_str);
+    message.unwrap_or(text.trim()).to_string()
+}
+
+async fn send(request: RequestBuilder) -> Result<Value> {
+    for attempt in 1..=MAX_RETRIES {
+        let retry = request.try_clone().expect("request bodies are buffered");
+        let response = retry.send().await?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            warn!("{}", tr!("gsheets.throttled", THROTTLE_DELAY_SECS * attempt as u64));
+            tokio::time::sleep(Duration::from_secs(THROTTLE_DELAY_SECS * attempt as u64)).await;
+            continue;
+        }
+        if !status.is_success() {
+            anyhow::bail!(tr!("gsheets.api_error", status.as_u16(), error_message(&text)));
+        }
+        return Ok(serde_json::from_str(&text).unwrap_or(Value::Null));
+    }
+    unreachable!()
+}
+
+async fn access_token(client: &Client, target: &Target) -> Result<String> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims { iss: &target.client_email, scope: SHEETS_SCOPE, aud: &target.token_uri, iat: now, exp: now + 3600 };
+    let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &target.key)?;
+    let form = [("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", assertion.as_str())];
+    let body = send(client.post(&target.token_uri).form(&form)).await?;
+    body.get("access_token").and_then(Value::as_str).map(str::to_string).ok_or_else(|| anyhow::anyhow!(t("gsheets.missing_token")))
+}
+
+// A1 notation needs tab titles quoted, with embedded quotes doubled
+fn quoted(title: &str) -> String {
+    format!("'{}'", title.replace('\'', "''"))
+}
+
+fn tab_rows(rows: &[&QueryResult], columns: &[Column], inactive_days: Option<i64>) -> Vec<Vec<String>> {
+    let mut values = vec![columns.iter().map(|column| column.header(true)).collect()];
+    for row in rows {
+        let record = columns.iter().map(|&column| match column {
+            Column::Dormant => column.value(row, inactive_days).to_uppercase(),
+            _ => column.value(row, inactive_days),
+        });
+        values.push(record.collect());
+    }
+    values
+}
+
+pub(crate) async fn write_tabs(
+    client: &Client,
+    target: &Target,
+    grouped: &HashMap<String, Vec<&QueryResult>>,
+    target_chains: &[String],
+    inactive_days: Option<i64>,
+    optional: &OptionalColumns,
+) -> Result<String> {
+    let columns = optional.sheet(&[Column::Address], ColumnData::new(grouped, inactive_days));
+    let tabs: Vec<(String, Vec<Vec<String>>)> = target_chains
+        .iter()
+        .filter_map(|chain| grouped.get(chain).map(|rows| (sheet_name(chain_display_name(chain)), tab_rows(rows, &columns, inactive_days))))
+        .collect();
+    let token = access_token(client, target).await?;
+    let url = format!("{}/{}", SHEETS_API, target.spreadsheet_id);
+
+    let body = send(client.get(&url).bearer_auth(&token).query(&[("fields", "sheets.properties(sheetId,title)")])).await?;
+    let existing: HashMap<String, i64> = body["sheets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|sheet| Some((sheet.pointer("/properties/title")?.as_str()?.to_string(), sheet.pointer("/properties/sheetId")?.as_i64()?)))
+        .collect();
+
+    // a tab left over from a previous run is cleared first, so none of its old rows survive a shorter result
+    let stale: Vec<String> = tabs.iter().filter(|(title, _)| existing.contains_key(title)).map(|(title, _)| quoted(title)).collect();
+    if !stale.is_empty() {
+        send(client.post(format!("{}/values:batchClear", url)).bearer_auth(&token).json(&json!({ "ranges": stale }))).await?;
+    }
+    let requests: Vec<Value> = tabs
+        .iter()
+        .map(|(title, values)| {
+            let grid = json!({ "rowCount": values.len(), "columnCount": values[0].len(), "frozenRowCount": 1 });
+            match existing.get(title) {
+                Some(id) => json!({ "updateSheetProperties": {
+                    "properties": { "sheetId": id, "gridProperties": grid },
+                    "fields": "gridProperties(rowCount,columnCount,frozenRowCount)",
+                }}),
+                None => json!({ "addSheet": { "properties": { "title": title, "gridProperties": grid } } }),
+            }
+        })
+        .collect();
+    if !requests.is_empty() {
+        send(client.post(format!("{}:batchUpdate", url)).bearer_auth(&token).json(&json!({ "requests": requests }))).await?;
+    }
+
+    for (title, values) in &tabs {
+        for (i, chunk) in values.chunks(target.chunk_rows).enumerate() {
+            let range = format!("{}!A{}", quoted(title), i * target.chunk_rows + 1);
+            let data = json!({ "valueInputOption": "RAW", "data": [{ "range": range, "values": chunk }] });
+            send(client.post(format!("{}/values:batchUpdate", url)).bearer_auth(&token).json(&data)).await?;
+        }
+        info!("{}", tr!("gsheets.tab_written", title, values.len() - 1));
+    }
+    Ok(format!("https://docs.google.com/spreadsheets/d/{}", target.spreadsheet_id))
+}
@@ -0,0 +1,391 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum Lang {
+    Zh,
+    En,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+// (key, zh, en); `{}` takes the next argument, `{0}`/`{1}`... a specific one
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("config.invalid_log_file", "LOG_FILE 无效: {}", "Invalid LOG_FILE: {}"),
+    ("config.concurrency_zero", "CONCURRENCY=0 无效：并发数至少为 1", "Invalid CONCURRENCY=0: concurrency must be at least 1"),
+    ("config.concurrency_clamped", "⚠ CONCURRENCY={} 过大，已限制为 {}", "⚠ CONCURRENCY={} is too high, capped at {}"),
+    ("config.concurrency_invalid", "⚠ CONCURRENCY={} 不是有效数字，使用默认值 {}", "⚠ CONCURRENCY={} is not a number, using the default {}"),
+    ("config.invalid_filter", "⚠ FILTER={} 无效（可选 active、inactive），输出全部结果", "⚠ Invalid FILTER={} (expected active or inactive), writing all results"),
+    ("config.invalid_secondary_provider", "SECONDARY_PROVIDER={} 无效，可选值: etherscan", "Invalid SECONDARY_PROVIDER={}, expected: etherscan"),
+    ("config.etherscan_missing_key", "SECONDARY_PROVIDER=etherscan 需要设置 ETHERSCAN_API_KEY", "SECONDARY_PROVIDER=etherscan requires ETHERSCAN_API_KEY"),
+    ("config.placeholders", "✓ 无交易行的 Hash 列显示 {}，时间列显示 {}", "✓ Rows without a transaction show {} as the hash and {} as the time"),
+    ("config.placeholder_status", "状态名称", "the status label"),
+    ("config.invalid_network", "NETWORK={} 无效，可选值: mainnet, testnet", "Invalid NETWORK={}, expected: mainnet, testnet"),
+    ("chain.duplicates", "⚠ TARGET_CHAINS 中有重复的链，已忽略: {}", "⚠ Duplicate chains in TARGET_CHAINS ignored: {}"),
+    ("chain.mixed_networks", "TARGET_CHAINS 同时包含主网和测试网（{}），请分开运行，或设置 NETWORK=testnet", "TARGET_CHAINS mixes mainnet and testnet chains ({}); run them separately or set NETWORK=testnet"),
+    ("chain.no_testnet", "链 {} 没有可用的测试网（NETWORK=testnet），支持: {}", "Chain {} has no testnet (NETWORK=testnet), supported: {}"),
+    ("chain.unknown_suggest", "⚠ 未知的链: {}，是否想输入 {}？", "⚠ Unknown chain: {}, did you mean {}?"),
+    ("chain.unknown", "⚠ 未知的链: {}", "⚠ Unknown chain: {}"),
+    ("chain.strict_unsupported", "TARGET_CHAINS 中有 {} 个不支持的链（STRICT_CHAINS=1），支持的链: {}", "TARGET_CHAINS contains {} unsupported chain(s) (STRICT_CHAINS=1), supported chains: {}"),
+    ("chain.supported", "  支持的链: {}", "  Supported chains: {}"),
+    ("config.invalid_timezone", "TIMEZONE 无效（需为 IANA 时区名，如 Asia/Shanghai）: {}", "Invalid TIMEZONE (expected an IANA zone name such as Asia/Shanghai): {}"),
+    ("config.invalid_time_format", "TIME_FORMAT 格式无效: {}", "Invalid TIME_FORMAT: {}"),
+    ("proxy.invalid", "代理地址无效 ({}): {}", "Invalid proxy URL ({}): {}"),
+    ("proxy.invalid_scheme", "代理地址无效 ({}): 需要 http/https/socks5/socks5h 协议和主机名", "Invalid proxy URL ({}): an http/https/socks5/socks5h scheme and a host are required"),
+    ("csv.delimiter", "✓ CSV 分隔符: {}", "✓ CSV delimiter: {}"),
+    ("csv.column_out_of_range", "{}={} 超出 CSV 列数（共 {} 列，序号从 0 开始）", "{}={} is beyond the CSV columns ({} columns, 0-based)"),
+    ("csv.column_name_without_header", "{}={} 是列名，但 CSV_HAS_HEADER=0 时只能使用列序号", "{}={} is a column name, but only column indexes are allowed with CSV_HAS_HEADER=0"),
+    ("csv.column_missing", "{} 指定的列 \"{}\" 不存在，可用的列: {}", "Column \"{1}\" set by {0} does not exist, available columns: {2}"),
+    ("csv.row_missing_column", "CSV 第 {} 行没有第 {} 列（CSV_ADDRESS_COLUMN）", "CSV line {} has no column {} (CSV_ADDRESS_COLUMN)"),
+    ("input.open_failed", "无法打开钱包文件 {}: {}", "Cannot open wallet file {}: {}"),
+    ("xlsx.sheet_missing", "XLSX_SHEET={} 在 {} 中不存在（现有工作表: {}）", "XLSX_SHEET={} not found in {} (sheets: {})"),
+    ("input.skipped_lines", "○ 跳过 {} 行空行或注释", "○ Skipped {} blank or comment line(s)"),
+    ("input.stdin", "标准输入", "stdin"),
+    ("input.file_rows", "○ {}: {} 行", "○ {}: {} row(s)"),
+    ("input.invalid_glob", "无效的文件通配符 {}: {}", "Invalid file glob {}: {}"),
+    ("input.glob_no_match", "通配符 {} 没有匹配到任何文件", "Glob {} did not match any file"),
+    ("input.not_found", "未找到钱包文件 ({} / {} / {} / {})", "Wallet file not found ({} / {} / {} / {})"),
+    ("input.private_key_derived", "🔑 私钥 → 地址: {} -> {}", "🔑 Private key → address: {} -> {}"),
+    ("input.private_keys_derived", "🔑 已从 {} 个私钥推导出地址", "🔑 Derived addresses from {} private keys"),
+    ("input.private_key_invalid", "⚠️  私钥解析失败: {}", "⚠️  Failed to parse private key: {}"),
+    ("input.invalid_private_key_line", "第 {} 行\t{}\t私钥解析失败", "line {}\t{}\tinvalid private key"),
+    ("input.unrecognized_line", "第 {} 行\t{}\t无法识别（既不是地址也不是私钥）", "line {}\t{}\tunrecognized (neither an address nor a private key)"),
+    ("input.malformed_line", "第 {} 行\t{}\t地址格式有误：{}", "line {}\t{}\tmalformed address: {}"),
+    ("input.malformed_address", "⚠ 第 {} 行看起来像地址但格式有误（{}），已跳过: {}", "⚠ Line {} looks like an address but is malformed ({}), skipped: {}"),
+    ("input.checksum_mismatch", "⚠ 第 {} 行地址 {} 的大小写与 EIP-55 校验和不符，可能有输错的字符；仍按 {} 查询", "⚠ Line {}: the case of {} does not match its EIP-55 checksum, a character may be mistyped; querying it as {}"),
+    ("address.non_hex", "第 {} 个字符 '{}' 不是十六进制", "character {} '{}' is not hex"),
+    ("address.bad_length", "0x 之后有 {} 位，应为 40 位", "{} hex digits after 0x, expected 40"),
+    ("input.invalid_written", "⚠ {} 行无法识别，已跳过并写入 {}", "⚠ {} unrecognized line(s) skipped and written to {}"),
+    ("input.duplicates", "⚠ 跳过 {} 个重复地址", "⚠ Skipped {} duplicate address(es)"),
+    ("input.limited", "✓ 只查询第 {} ~ {} 个地址（共 {} 个），本次查询 {} 个", "✓ Querying addresses {} ~ {} of {}, {} in this run"),
+    ("input.limit_empty", "⚠ 跳过前 {} 个地址后没有剩余地址（共 {} 个）", "⚠ No addresses left after skipping the first {} (of {})"),
+    ("input.filtered", "✓ ADDRESS_FILTER={}: 保留 {} 个地址，过滤掉 {} 个", "✓ ADDRESS_FILTER={}: kept {} address(es), dropped {}"),
+    ("config.invalid_address_filter", "ADDRESS_FILTER 不是有效的正则表达式 ({}): {}", "ADDRESS_FILTER is not a valid regular expression ({}): {}"),
+    ("input.loaded", "✓ 从 {} 读取到 {} 个地址", "✓ Loaded {1} address(es) from {0}"),
+    ("validate.file", "✓ 文件: {} (共 {} 行)", "✓ File: {} ({} lines)"),
+    ("validate.addresses", "✓ 有效地址: {}", "✓ Valid addresses: {}"),
+    ("validate.private_keys", "✓ 有效私钥: {}", "✓ Valid private keys: {}"),
+    ("validate.unrecognized", "{} 无法识别: {}", "{} Unrecognized: {}"),
+    ("validate.malformed_line", "  第 {} 行: {}（地址格式有误：{}）", "  line {}: {} (malformed address: {})"),
+    ("validate.unrecognized_line", "  第 {} 行: {}", "  line {}: {}"),
+    ("validate.failed", "{} 中有 {} 行无法识别", "{} has {} unrecognized line(s)"),
+    ("resolve.written", "✓ 已写入 {} 个地址至 {}", "✓ Wrote {} address(es) to {}"),
+    ("time.parse_failed", "时间解析失败", "invalid timestamp"),
+    ("time.out_of_range", "时间格式错误", "timestamp out of range"),
+    ("status.found", "有交易", "Found"),
+    ("status.no_transactions", "无交易", "No transactions"),
+    ("status.no_data", "无数据", "No data"),
+    ("status.parse_error", "解析失败", "Parse error"),
+    ("status.network_error", "网络错误", "Network error"),
+    ("status.timeout", "超时", "Timeout"),
+    ("account.contract", "合约", "Contract"),
+    ("direction.in", "转入", "In"),
+    ("direction.out", "转出", "Out"),
+    ("direction.self", "自转", "Self"),
+    ("direction.contract_creation", "创建合约", "Contract creation"),
+    ("direction.other", "其他", "Other"),
+    ("checkpoint.mismatch", "⚠ 断点文件 {} 与当前输入不匹配，已忽略", "⚠ Checkpoint {} does not match the current input, ignored"),
+    ("cache.hit", "✓ 使用结果缓存 {}（{} 生成，{} 条结果），跳过查询；用 --refresh 重新查询", "✓ Using results cache {} (created {}, {} results), skipping queries; pass --refresh to query again"),
+    ("cache.mismatch", "○ 结果缓存 {} 与当前地址、链或查询配置不匹配，重新查询", "○ Results cache {} does not match the current addresses, chains or query settings, querying again"),
+    ("cache.read_failed", "⚠ 读取结果缓存 {} 失败，重新查询: {}", "⚠ Failed to read results cache {}, querying again: {}"),
+    ("cache.saved", "✓ 已写入结果缓存 {}（{} 条结果）", "✓ Results cache written to {} ({} results)"),
+    ("cache.write_failed", "⚠ 写入结果缓存 {} 失败: {}", "⚠ Failed to write results cache {}: {}"),
+    ("checkpoint.write_failed", "⚠ 写入断点文件失败: {}", "⚠ Failed to write checkpoint: {}"),
+    ("retry.unsupported_file", "不支持的文件类型: {}（仅支持 xlsx/json/csv）", "Unsupported file type: {} (xlsx/json/csv only)"),
+    ("quota.low", "⚠ 服务商返回的剩余请求额度只有 {}（低于 QUOTA_LOW_REMAINING={}），{}", "⚠ The provider reports only {} requests left (below QUOTA_LOW_REMAINING={}), {}"),
+    ("quota.slowing", "降低并发以免额度耗尽", "reducing concurrency to avoid running out"),
+    ("quota.no_adaptive", "设置 ADAPTIVE_CONCURRENCY=1 可自动降低并发", "set ADAPTIVE_CONCURRENCY=1 to slow down automatically"),
+    ("concurrency.throttled", "⚠ 触发限流 (429)，并发数降至 {}", "⚠ Rate limited (429), concurrency reduced to {}"),
+    ("proxy.direct", "直连", "direct"),
+    ("proxy.cooldown", "⚠ 代理 {} 连续失败 {} 次，暂停使用 {} 秒", "⚠ Proxy {} failed {} times in a row, pausing it for {} seconds"),
+    ("proxy.stats", "代理池统计:", "Proxy pool stats:"),
+    ("proxy.stats_line", "  {} — 请求 {} 次，失败 {} 次（{}%）", "  {} — {} requests, {} failed ({}%)"),
+    ("log.sending_request", "发送请求", "sending request"),
+    ("query.found", "✓ {} on {}: {} @ {}", "✓ {} on {}: {} @ {}"),
+    ("query.rechecking", "⚠ {} on {}: 初次查询无交易，重新确认中...", "⚠ {} on {}: no transactions on first query, re-checking..."),
+    ("query.no_transactions", "○ {} on {}: 无交易", "○ {} on {}: no transactions"),
+    ("query.token_found", "✓ {} on {}: {} 转账 {} @ {}", "✓ {} on {}: {} transfer {} @ {}"),
+    ("query.no_token_transfers", "○ {} on {}: 无代币转账", "○ {} on {}: no token transfers"),
+    ("query.custom_found", "✓ {} on {}: {}", "✓ {} on {}: {}"),
+    ("query.custom_empty", "○ {} on {}: 结果中没有 {}", "○ {} on {}: nothing at {} in the result"),
+    ("query.custom_error", "⚠ {} on {}: 接口返回错误，记为无数据: {}", "⚠ {} on {}: the method returned an error; recorded as no data: {}"),
+    ("query.parse_retry", "⚠ JSON 解析失败 ({} on {}, 第 {} 次重试): {}", "⚠ JSON parse error ({} on {}, retry {}): {}"),
+    ("query.parse_failed", "✗ JSON 解析失败 (地址: {}): {}", "✗ JSON parse error (address: {}): {}"),
+    ("query.first_tx_failed", "⚠ 首次交易查询失败 ({} on {})，活跃跨度留空: {}", "⚠ First transaction lookup failed ({} on {}), span left empty: {}"),
+    ("query.response_saved", "○ 原始响应已保存到 {}", "○ Raw response saved to {}"),
+    ("query.response_save_failed", "⚠ 无法保存原始响应到 {}: {}", "⚠ Could not save the raw response to {}: {}"),
+    ("query.network_retry", "⚠ 网络错误 ({} on {}, 第 {} 次重试): {}", "⚠ Network error ({} on {}, retry {}): {}"),
+    ("query.network_failed", "✗ 网络错误 (地址: {}): {}", "✗ Network error (address: {}): {}"),
+    ("query.timeout_retry", "⚠ 请求超时 ({} on {}, 第 {} 次重试): 超过 {} 秒", "⚠ Request timed out ({} on {}, retry {}): over {} seconds"),
+    ("query.timeout_failed", "✗ 请求超时 (地址: {}): 超过 {} 秒", "✗ Request timed out (address: {}): over {} seconds"),
+    ("query.throttled_retry", "⚠ 请求被限流或服务端出错 ({} on {}, 第 {} 次重试): HTTP {}", "⚠ Throttled or server error ({} on {}, retry {}): HTTP {}"),
+    ("query.throttled_failed", "✗ 请求被限流或服务端出错 (地址: {}): HTTP {}", "✗ Throttled or server error (address: {}): HTTP {}"),
+    ("solana.found", "✓ {} on Solana: {} @ {}", "✓ {} on Solana: {} @ {}"),
+    ("solana.no_transactions", "○ {} on Solana: 无交易", "○ {} on Solana: no transactions"),
+    ("error.parse", "JSON 解析失败: {}", "JSON parse error: {}"),
+    ("error.network", "网络错误: {}", "network error: {}"),
+    ("error.timeout", "请求超时: 超过 {} 秒", "request timed out: over {} seconds"),
+    ("solana.retry", "⚠ {} on Solana, 第 {} 次重试: {}", "⚠ {} on Solana, retry {}: {}"),
+    ("solana.failed", "✗ {} on Solana: {}", "✗ {} on Solana: {}"),
+    ("error.http_status", "HTTP {}: {}", "HTTP {}: {}"),
+    ("error.missing_result", "响应中没有 result", "response has no result"),
+    ("account.classify_failed", "⚠ 无法判断账户类型 ({} on {}): {}", "⚠ Cannot determine account type ({} on {}): {}"),
+    ("query.empty_result_retry", "⚠ {} 的多链响应既没有 result 也没有 error，重试一次", "⚠ Multichain response for {} has neither result nor error, retrying once"),
+    ("query.empty_result", "⚠ {} 重试后 result 仍为空，记为无数据（不等同于无交易）", "⚠ {} still has an empty result after a retry; recorded as no data (not the same as no transactions)"),
+    ("secondary.used", "○ {} 在 {} 上采用 {} 的结果（最后交易 {}）", "○ {} on {}: using the {} result (last tx {})"),
+    ("secondary.failed", "⚠ {} 查询 {} 在 {} 上失败，只使用主服务商的结果: {}", "⚠ {} lookup for {} on {} failed, keeping the primary result: {}"),
+    ("secondary.preflight_ok", "✓ {} 连通性检查通过", "✓ {} health check passed"),
+    ("secondary.preflight_failed", "✗ {} 的 API key 无效或网络不可达: {}（可用 --no-preflight 跳过检查）", "✗ {} API key invalid or network unreachable: {} (use --no-preflight to skip this check)"),
+    ("secondary.mode_skipped", "⚠ QUERY_MODE={} 不支持 SECONDARY_PROVIDER，只查询主服务商", "⚠ QUERY_MODE={} does not support SECONDARY_PROVIDER, only the primary provider is queried"),
+    ("query.rpc_error", "⚠ {} 的多链查询返回错误，记为无数据: {}", "⚠ Multichain query for {} returned an error; recorded as no data: {}"),
+    ("query.no_records_confirmed", "○ {} on {}: 无交易记录 (已确认)", "○ {} on {}: no transaction records (confirmed)"),
+    ("query.no_transactions_confirmed", "○ {} on {}: 无交易 (已确认)", "○ {} on {}: no transactions (confirmed)"),
+    ("error.id_mismatch", "响应 id 不匹配 (期望 {}, 实际 {})", "response id mismatch (expected {}, got {})"),
+    ("multichain.parse_retry", "⚠ JSON 解析失败 ({} on 多链, 第 {} 次重试): {}", "⚠ JSON parse error ({} on multichain, retry {}): {}"),
+    ("multichain.network_retry", "⚠ 网络错误 ({} on 多链, 第 {} 次重试): {}", "⚠ Network error ({} on multichain, retry {}): {}"),
+    ("multichain.timeout_retry", "⚠ 请求超时 ({} on 多链, 第 {} 次重试): 超过 {} 秒", "⚠ Request timed out ({} on multichain, retry {}): over {} seconds"),
+    ("multichain.throttled_retry", "⚠ 请求被限流或服务端出错 ({} on 多链, 第 {} 次重试): HTTP {}", "⚠ Throttled or server error ({} on multichain, retry {}): HTTP {}"),
+    ("batch.throttled_retry", "⚠ 批量请求被限流 ({} 个地址, 第 {} 次重试): HTTP {}", "⚠ Batch request throttled ({} addresses, retry {}): HTTP {}"),
+    ("batch.failed", "⚠ 批量请求失败 ({} 个地址)，改为逐个查询: {}", "⚠ Batch request failed ({} addresses), querying one by one: {}"),
+    ("batch.missing", "⚠ 批量响应中缺少 {}，改为单独查询", "⚠ {} missing from batch response, querying it separately"),
+    ("batch.unknown_id", "⚠ 批量响应中有无法对应请求的 id {}，已忽略", "⚠ Batch response has an id {} that matches no request; ignored"),
+    ("batch.duplicate_id", "⚠ 批量响应中 {} 的结果重复，只使用第一个", "⚠ Batch response has {} more than once; using the first"),
+    ("header.label", "备注", "Label"),
+    ("header.account_type", "账户类型", "Account Type"),
+    ("header.wallet_address", "钱包地址", "Wallet Address"),
+    ("header.last_tx_time", "最后交易时间 ({})", "Last Tx Time ({})"),
+    ("header.tx_hash", "交易 Hash", "Tx Hash"),
+    ("header.dormant", "是否休眠", "Dormant"),
+    ("header.first_tx_time", "首次交易时间 ({})", "First Tx Time ({})"),
+    ("header.first_tx", "首次交易", "First Tx"),
+    ("header.span_days", "活跃跨度(天)", "Span (days)"),
+    ("header.token", "代币", "Token"),
+    ("header.token_contract", "代币合约", "Token Contract"),
+    ("header.result", "结果", "Result"),
+    ("header.provider", "数据来源", "Provider"),
+    ("header.latency", "耗时(ms)", "Latency (ms)"),
+    ("header.direction", "方向", "Direction"),
+    ("header.from", "发送方", "From"),
+    ("header.to", "接收方", "To"),
+    ("header.value", "金额", "Value"),
+    ("header.interacted", "交互过 {}", "Interacted {}"),
+    ("sheet.by_address", "按地址", "By Address"),
+    ("header.chain_last_tx_time", "{} 最后交易时间", "{} Last Tx Time"),
+    ("header.chain_dormant", "{} 是否休眠", "{} Dormant"),
+    ("sheet.all", "全部", "All"),
+    ("header.chain", "链", "Chain"),
+    ("header.address", "地址", "Address"),
+    ("header.time", "时间", "Time"),
+    ("header.status", "状态", "Status"),
+    ("sheet.summary", "汇总", "Summary"),
+    ("sheet.activity", "活跃度", "Activity"),
+    ("sheet.contracts", "合约交互", "Contracts"),
+    ("sheet.changes", "变化", "Changes"),
+    ("sheet.errors", "错误明细", "Errors"),
+    ("header.change", "变化", "Change"),
+    ("header.previous_tx_time", "上次最后交易时间 ({})", "Previous Last Tx Time ({})"),
+    ("header.previous_tx_hash", "上次交易 Hash", "Previous Tx Hash"),
+    ("header.error_type", "错误类型", "Error Type"),
+    ("header.count", "数量", "Count"),
+    ("header.error_message", "错误信息", "Error Message"),
+    ("change.activated", "变为活跃", "Activated"),
+    ("change.new_transaction", "有新交易", "New transaction"),
+    ("change.deactivated", "变为无交易", "No longer active"),
+    ("change.new_address", "新增地址", "New address"),
+    ("summary.total_addresses", "地址总数", "Total addresses"),
+    ("summary.elapsed", "总耗时 (秒)", "Elapsed (s)"),
+    ("summary.active", "活跃", "Active"),
+    ("summary.errors", "错误", "Errors"),
+    ("summary.error_type", "错误类型", "Error type"),
+    ("summary.count", "数量", "Count"),
+    ("summary.latency_p50", "请求耗时 p50 (ms)", "Request latency p50 (ms)"),
+    ("summary.latency_p95", "请求耗时 p95 (ms)", "Request latency p95 (ms)"),
+    ("config.missing_api_key", "⚠️  警告: 未设置 ANKR_API_KEY", "⚠️  Warning: ANKR_API_KEY is not set"),
+    ("config.missing_api_key_env", "请在 .env 文件中设置: ANKR_API_KEY=your_api_key", "Set it in the .env file: ANKR_API_KEY=your_api_key"),
+    ("config.missing_api_key_var", "或设置环境变量: set ANKR_API_KEY=your_api_key", "or as an environment variable: set ANKR_API_KEY=your_api_key"),
+    ("config.api_key_format", "API 密钥格式: https://rpc.ankr.com/multichain/{{your_api_key}}", "API key format: https://rpc.ankr.com/multichain/{{your_api_key}}"),
+    ("config.user_agent", "✓ User-Agent: {}", "✓ User-Agent: {}"),
+    ("config.api_key_loaded", "✓ 已加载 ANKR_API_KEY（{}...）", "✓ Loaded ANKR_API_KEY ({}...)"),
+    ("config.proxy", "✓ 代理: {}", "✓ Proxy: {}"),
+    ("config.proxy_pool", "✓ 代理池: {} 个代理轮询", "✓ Proxy pool: rotating across {} proxies"),
+    ("config.http2_only", "✓ 仅使用 HTTP/2", "✓ HTTP/2 only"),
+    ("config.concurrency", "✓ 并发数: {}", "✓ Concurrency: {}"),
+    ("config.chain_concurrency", "✓ 单链并发上限: {}", "✓ Per-chain concurrency caps: {}"),
+    ("config.adaptive", "✓ 自适应并发: {} ~ {}", "✓ Adaptive concurrency: {} ~ {}"),
+    ("config.rate_limit", "✓ 速率限制: {} 请求/秒", "✓ Rate limit: {} requests/s"),
+    ("config.query_mode", "✓ 查询模式: {}", "✓ Query mode: {}"),
+    ("config.custom_method", "✓ 自定义方法: {} (结果路径: {})", "✓ Custom method: {} (result path: {})"),
+    ("config.query_span", "✓ 同时查询首次交易，输出活跃跨度 (QUERY_SPAN=1)", "✓ Also querying the first transaction for the activity span (QUERY_SPAN=1)"),
+    ("config.skip_confirm", "✓ 多链模式下不再单独确认无交易的链 (SKIP_CONFIRM=1)", "✓ Chains missing from multichain results are not re-confirmed (SKIP_CONFIRM=1)"),
+    ("config.confirm_empty", "✓ 单链模式下首次无交易的结果在 {} 秒后再确认一次", "✓ Single-chain mode re-checks empty results once after {} seconds"),
+    ("config.batches", "✓ 分批查询: 每批 {} 个地址，批次间暂停 {} 秒", "✓ Batches: {} addresses each, {} seconds between batches"),
+    ("config.testnet", "✓ 网络: 测试网", "✓ Network: testnet"),
+    ("config.time_format", "✓ 时间格式: {} ({})", "✓ Time format: {} ({})"),
+    ("retry.loaded", "✓ 从 {} 读取 {} 条结果，其中 {} 条失败，将重新查询", "✓ Read {1} results from {0}, re-querying {2} failed ones"),
+    ("diff.loaded", "✓ 从 {} 读取 {} 条上次结果，用于比较变化", "✓ Read {1} previous results from {0} to compare against"),
+    ("diff.summary", "○ 与上次相比有 {} 处变化：变为活跃 {}，有新交易 {}，变为无交易 {}，新增地址 {}", "○ {} changes since the previous run: {} activated, {} new transactions, {} no longer active, {} new addresses"),
+    ("diff.unmatched_chains", "⚠ 链 {} 只出现在其中一次运行中，不参与比较", "⚠ Chains {} appear in only one of the runs and are not compared"),
+    ("config.chains", "✓ 目标链: {}", "✓ Target chains: {}"),
+    ("checkpoint.resumed", "✓ 从断点 {} 恢复 {} 条结果，跳过已完成的查询", "✓ Resumed {1} results from checkpoint {0}, skipping completed queries"),
+    ("account.classifying", "正在判断账户类型 (eth_getCode on {}, 地址数量: {})...", "Classifying accounts (eth_getCode on {}, addresses: {})..."),
+    ("account.summary", "✓ EOA: {}，合约: {}", "✓ EOA: {}, contracts: {}"),
+    ("run.single_mode", "使用单链查询模式... (链数量: {}, 地址数量: {})", "Using single-chain mode... (chains: {}, addresses: {})"),
+    ("run.multi_mode", "使用多链同时查询模式... (链数量: {}, 地址数量: {})", "Using multichain mode... (chains: {}, addresses: {})"),
+    ("run.tokens_mode", "使用代币转账查询模式... (链数量: {}, 地址数量: {})", "Using token-transfer mode... (chains: {}, addresses: {})"),
+    ("run.mode_skips_solana", "⚠ {} 模式不支持 Solana，跳过 {} 个 Solana 地址", "⚠ {} mode does not support Solana, skipping {} Solana address(es)"),
+    ("run.custom_mode", "使用自定义方法查询模式... (链数量: {}, 地址数量: {})", "Using custom method mode... (chains: {}, addresses: {})"),
+    ("run.solana", "查询 Solana... (地址数量: {})", "Querying Solana... (addresses: {})"),
+    ("run.batch", "批次 {}/{}: {} 个地址", "Batch {}/{}: {} address(es)"),
+    ("run.batch_pause", "批次间暂停 {} 秒...", "Pausing {} seconds between batches..."),
+    ("preflight.ok", "✓ API 连通性检查通过 ({}, {} ms)", "✓ API health check passed ({}, {} ms)"),
+    ("preflight.failed", "✗ API key 无效或网络不可达 ({}): {}（可用 --no-preflight 跳过检查）", "✗ API key invalid or network unreachable ({}): {} (use --no-preflight to skip this check)"),
+    ("activity.fetching", "○ 正在分页拉取交易历史: {} 个地址，每个地址最多 {} 页（每页 {} 条交易）", "○ Paging through transaction history: {} addresses, up to {} pages each ({} transactions per page)"),
+    ("activity.retry", "⚠ {} 交易历史分页请求失败 (第 {} 次): {}", "⚠ History page request for {} failed (attempt {}): {}"),
+    ("activity.failed", "✗ {} 交易历史分页请求最终失败，只统计已获取的页: {}", "✗ History page request for {} failed for good, keeping the pages fetched so far: {}"),
+    ("activity.truncated", "⚠ {} 个地址的交易超过 {} 页或分页失败，只统计了最近的部分（未找到交互的合约记为未知）", "⚠ {} address(es) have more than {} pages of transactions or a failed page; only the most recent part is counted (contracts not seen are left unknown)"),
+    ("config.invalid_target_contract", "TARGET_CONTRACT 中的 {} 不是有效的合约地址", "{} in TARGET_CONTRACT is not a valid contract address"),
+    ("explorers.loaded", "✓ 已从 {} 读取区块浏览器链接模板（交易 {} 条，地址 {} 条）", "✓ Loaded explorer link templates from {} ({} tx, {} address)"),
+    ("explorers.read_failed", "无法读取区块浏览器配置 {}: {}", "Cannot read explorer config {}: {}"),
+    ("explorers.parse_failed", "区块浏览器配置 {} 格式错误: {}", "Invalid explorer config {}: {}"),
+    ("explorers.missing_placeholder", "区块浏览器配置 {} 中 {} 的模板 {} 缺少 {} 占位符", "Explorer config {}: template for {} ({}) is missing the {} placeholder"),
+    ("config.invalid_provider", "PROVIDER 的值 {} 无效，可选 ankr、mock", "Invalid PROVIDER {}, expected ankr or mock"),
+    ("config.custom_missing_method", "QUERY_MODE=custom 需要设置 RPC_METHOD", "QUERY_MODE=custom requires RPC_METHOD"),
+    ("config.custom_invalid_template", "RPC_PARAMS_TEMPLATE 不是有效的 JSON: {}", "RPC_PARAMS_TEMPLATE is not valid JSON: {}"),
+    ("config.custom_invalid_path", "RPC_RESULT_PATH 应为以 / 开头的 JSON Pointer（如 /transactions/0/hash），当前为 {}", "RPC_RESULT_PATH must be a JSON Pointer starting with / (e.g. /transactions/0/hash), got {}"),
+    ("provider.mock", "✓ 离线模式：从 {} 读取了 {} 条模拟结果，不会发起网络请求", "✓ Offline mode: loaded canned results from {} ({} entries), no network requests will be made"),
+    ("provider.fixtures_read_failed", "无法读取模拟数据文件 {}: {}", "Cannot read fixtures file {}: {}"),
+    ("provider.fixtures_parse_failed", "模拟数据文件 {} 格式错误: {}", "Invalid fixtures file {}: {}"),
+    ("gsheets.missing_spreadsheet", "OUTPUT_FORMAT 包含 gsheets 时必须设置 GSHEETS_SPREADSHEET_ID", "GSHEETS_SPREADSHEET_ID must be set when OUTPUT_FORMAT includes gsheets"),
+    ("gsheets.missing_credentials", "OUTPUT_FORMAT 包含 gsheets 时必须用 GOOGLE_APPLICATION_CREDENTIALS 指定服务账号密钥文件", "GOOGLE_APPLICATION_CREDENTIALS must point to a service account key when OUTPUT_FORMAT includes gsheets"),
+    ("gsheets.credentials_read_failed", "无法读取服务账号密钥 {}: {}", "Cannot read service account key {}: {}"),
+    ("gsheets.credentials_parse_failed", "服务账号密钥 {} 格式错误: {}", "Invalid service account key {}: {}"),
+    ("gsheets.target", "✓ 结果将写入 Google 表格 {}（服务账号 {}，需已共享编辑权限）", "✓ Results will be written to Google Sheet {} (service account {}, which needs edit access)"),
+    ("gsheets.missing_token", "Google 授权响应中没有 access_token", "Google token response has no access_token"),
+    ("gsheets.api_error", "Google Sheets 接口返回 HTTP {}: {}", "Google Sheets API returned HTTP {}: {}"),
+    ("gsheets.throttled", "⚠ Google Sheets 接口限流，{} 秒后重试", "⚠ Google Sheets API rate limited, retrying in {}s"),
+    ("gsheets.tab_written", "✓ 已写入 Google 表格工作表 {}（{} 行）", "✓ Wrote Google Sheet tab {} ({} rows)"),
+    ("notify.completed", "✓ 钱包查询完成：{} 个地址，活跃 {}，无交易 {}，错误 {} 条，耗时 {} 秒。结果: {}", "✓ Wallet check finished: {} addresses, {} active, {} inactive, {} errors, took {}s. Results: {}"),
+    ("notify.interrupted", "⚠ 钱包查询被中断：{} 个地址，活跃 {}，无交易 {}，错误 {} 条，耗时 {} 秒。已保存部分结果: {}", "⚠ Wallet check interrupted: {} addresses, {} active, {} inactive, {} errors, took {}s. Partial results: {}"),
+    ("notify.sent", "✓ 已发送完成通知", "✓ Completion notification sent"),
+    ("notify.failed", "⚠ 完成通知发送失败: {}", "⚠ Could not send the completion notification: {}"),
+    ("notify.telegram_incomplete", "⚠ Telegram 通知需要同时设置 TELEGRAM_BOT_TOKEN 和 TELEGRAM_CHAT_ID，已忽略", "⚠ Telegram notifications need both TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID; ignoring"),
+    ("notify.telegram_too_large", "⚠ {} 大小为 {} MB，超过 Telegram 的 {} MB 上限，只发送文字摘要", "⚠ {} is {} MB, over Telegram's {} MB limit; sending the text summary only"),
+    ("notify.telegram_sent", "✓ 已发送 Telegram 通知", "✓ Telegram notification sent"),
+    ("notify.telegram_failed", "⚠ Telegram 通知发送失败: {}", "⚠ Could not send the Telegram notification: {}"),
+    ("run.ctrl_c", "⚠ 收到 Ctrl-C，停止发起新查询，最多等待 {} 秒让进行中的请求完成（再按一次强制退出）", "⚠ Ctrl-C received, no new queries will start; waiting up to {} seconds for in-flight requests (press again to force exit)"),
+    ("run.deadline", "⚠ 已达到运行时限 MAX_RUNTIME_SECS={} 秒，停止发起新查询，最多等待 {} 秒让进行中的请求完成", "⚠ Reached the MAX_RUNTIME_SECS={} second limit, no new queries will start; waiting up to {} seconds for in-flight requests"),
+    ("run.deadline_failed", "运行时限已到，{} / {} 个地址未查询", "Run time limit reached with {} of {} addresses not queried"),
+    ("run.deadline_unqueried", "⚠ 因运行时限，{} / {} 个地址未查询", "⚠ {} of {} addresses were not queried because of the run time limit"),
+    ("run.force_exit", "✗ 强制退出", "✗ Forced exit"),
+    ("run.interrupted", "⚠ 查询已中断，{} 条结果已保存至 {}；重新运行将从断点 {} 继续", "⚠ Interrupted, {} results saved to {}; run again to resume from checkpoint {}"),
+    ("run.error_threshold", "{} / {} 条查询失败 ({}%)，超过允许的 {}%", "{} of {} queries failed ({}%), above the allowed {}%"),
+    ("output.filtered", "○ FILTER={}：输出 {} 行，排除 {} 行（汇总和运行统计仍包含全部结果）", "○ FILTER={}: writing {} rows, {} excluded (the summary and run statistics still count all results)"),
+    ("output.append_read_failed", "无法读取已有的输出文件 {}: {}", "Cannot read the existing output file {}: {}"),
+    ("output.append_kept", "✓ 追加模式: 保留 {} 中已有的 {} 个工作表", "✓ Append mode: keeping {1} existing worksheet(s) from {0}"),
+    ("output.unexpected_chains", "⚠ 结果中包含不在目标链中的链: {}，已追加到输出末尾", "⚠ Results include chains that were not requested: {}; appended at the end of the output"),
+    ("output.unknown_column", "⚠ OUTPUT_COLUMNS 中的列 {} 不存在，已忽略（可用: {}）", "⚠ Unknown column {} in OUTPUT_COLUMNS, ignored (available: {})"),
+    ("output.no_columns", "⚠ OUTPUT_COLUMNS 中没有可用的列，使用默认列", "⚠ OUTPUT_COLUMNS has no usable columns; using the default columns"),
+    ("output.columns_unavailable", "⚠ 本次运行没有这些列的数据，输出中将省略: {}", "⚠ This run has no data for these columns, leaving them out: {}"),
+    ("manifest.saved", "✓ 运行清单已保存至 {}", "✓ Run manifest saved to {}"),
+    ("manifest.failed", "⚠ 无法写入运行清单 {}: {}", "⚠ Cannot write the run manifest {}: {}"),
+    ("output.save_failed", "⚠ 无法保存 {}（文件可能正被 Excel 打开）: {}", "⚠ Cannot save {} (is it open in Excel?): {}"),
+    ("output.sidecar_saved", "⚠ 已先把 {} 条结果保存至 {}", "⚠ Saved {} results to {} first"),
+    ("output.sidecar_failed", "✗ 无法写入 {}: {}", "✗ Cannot write {}: {}"),
+    ("output.saved_alternate", "⚠ 结果改为保存至 {}", "⚠ Saved the workbook as {} instead"),
+    ("output.save_gave_up", "无法保存 {}: {}；结果见 {}", "Cannot save {}: {}; the results are in {}"),
+    ("output.stream_write_failed", "⚠ 写入流式输出失败: {}", "⚠ Failed to write streaming output: {}"),
+    ("stats.header", "—— 运行统计 ——", "—— Run summary ——"),
+    ("stats.addresses", "地址: {}，活跃: {}，无交易: {}", "Addresses: {}, active: {}, inactive: {}"),
+    ("stats.errors", "错误: {}", "Errors: {}"),
+    ("stats.separator", "，", ", "),
+    ("stats.requests", "请求数: {}（其中 429 限流 {} 次）", "Requests: {} ({} rate-limited with 429)"),
+    ("stats.latency", "请求耗时: p50 {} ms，p95 {} ms", "Request latency: p50 {} ms, p95 {} ms"),
+    ("stats.quota", "服务商额度: 最低剩余 {}，下次重置 {}", "Provider quota: lowest remaining {}, next reset {}"),
+    ("stats.elapsed", "耗时: {} 秒", "Elapsed: {} s"),
+    ("run.done", "查询完成！结果已保存至 {}", "Done! Results saved to {}"),
+    ("dry_run.header", "—— 试运行：只打印查询计划，不发起任何网络请求 ——", "—— Dry run: printing the query plan only, no network requests ——"),
+    ("dry_run.addresses", "地址: {} 个（EVM {}，Solana {}）", "Addresses: {} (EVM {}, Solana {})"),
+    ("dry_run.chains", "计划查询的链: {}", "Planned chains: {}"),
+    ("dry_run.skipped", "已完成 {} 个查询（断点/上次结果），实际运行时会跳过", "{} queries already done (checkpoint/previous results) will be skipped"),
+    ("dry_run.classify", "账户类型判断: {} 次 eth_getCode 请求", "Account classification: {} eth_getCode requests"),
+    ("dry_run.single", "单链模式: {} 次请求", "Single-chain mode: {} requests"),
+    ("dry_run.confirm_empty", "  无交易的地址/链会再确认一次 (EMPTY_RECHECK=on)", "  empty results are rechecked once (EMPTY_RECHECK=on)"),
+    ("dry_run.multi", "多链模式: {} 个地址，{} 个批量请求（每批最多 {} 个地址）", "Multichain mode: {} addresses in {} batch requests (up to {} addresses each)"),
+    ("dry_run.solana", "Solana: {} 次请求", "Solana: {} requests"),
+    ("dry_run.activity", "交易历史分页 (FETCH_ALL / TARGET_CONTRACT): {} 个地址，{} 到 {} 次请求", "Transaction history paging (FETCH_ALL / TARGET_CONTRACT): {} addresses, {} to {} requests"),
+    ("dry_run.total", "预计请求数: 至少 {}（不含重试）", "Estimated requests: at least {} (excluding retries)"),
+    ("dry_run.output", "结果将写入 {}，断点文件 {}", "Results would be written to {}, checkpoint {}"),
+];
+
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::Zh)
+}
+
+pub fn t(key: &'static str) -> &'static str {
+    t_in(lang(), key)
+}
+
+pub fn t_in(lang: Lang, key: &'static str) -> &'static str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, zh, en)) => match lang {
+            Lang::Zh => zh,
+            Lang::En => en,
+        },
+        None => key,
+    }
+}
+
+// every translation of `key`, for reading back files written in either language
+pub fn t_all(key: &'static str) -> [&'static str; 2] {
+    [t_in(Lang::Zh, key), t_in(Lang::En, key)]
+}
+
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut index = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    index.push(c);
+                }
+                let position = if index.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    index.parse().unwrap_or(usize::MAX)
+                };
+                if let Some(arg) = args.get(position) {
+                    out.push_str(&arg.to_string());
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+macro_rules! tr {
+    ($key:expr $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::fill($crate::i18n::t($key), &[$(&$arg as &dyn std::fmt::Display),*])
+    };
+}
+
+pub(crate) use tr;
@@ -0,0 +1,5044 @@
+pub mod cli;
+mod etherscan;
+mod gsheets;
+pub mod i18n;
+mod notify;
+
+use anyhow::Result;
+use calamine::Reader as _;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use csv::{ReaderBuilder, StringRecord};
+use ethers::signers::Signer;
+use futures::future::join_all;
+use i18n::{t, tr, Lang};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use regex::Regex;
+use reqwest::{Client, StatusCode};
+use rust_xlsxwriter::{Color, ConditionalFormat2ColorScale, Format, Url, Workbook, Worksheet};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::error::Elapsed;
+use tokio::time::{timeout, Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+const ANKR_RPC_BASE: &str = "https://rpc.ankr.com/multichain";
+const ANKR_SOLANA_RPC_BASE: &str = "https://rpc.ankr.com/solana";
+const ANKR_CHAIN_RPC_BASE: &str = "https://rpc.ankr.com";
+const SOLANA_CHAIN: &str = "solana";
+const ALL_CHAINS: &str = "all";
+const WALLET_FILE: &str = "data/wallets.csv";
+const WALLET_TXT_FILE: &str = "data/wallets.txt";
+const WALLET_JSON_FILE: &str = "data/wallets.json";
+const WALLET_YAML_FILE: &str = "data/wallets.yaml";
+const CHECKPOINT_FILE: &str = ".checkpoint.jsonl";
+const OUTPUT_FILE: &str = "wallet_last_tx.xlsx";
+const RUN_MANIFEST_FILE: &str = "run_manifest.json";
+const RESULTS_CACHE_FILE: &str = ".results_cache.json";
+const MAX_SHEET_NAME_CHARS: usize = 31;
+const DEFAULT_OUTPUT_DIR: &str = "out";
+const STREAM_CSV_FILE: &str = "wallet_last_tx.csv";
+const STREAM_JSONL_FILE: &str = "wallet_last_tx.jsonl";
+const ACTIVITY_CSV_FILE: &str = "wallet_activity.csv";
+const CONTRACTS_CSV_FILE: &str = "wallet_contracts.csv";
+const CHANGES_CSV_FILE: &str = "wallet_changes.csv";
+const INVALID_INPUTS_FILE: &str = "invalid_inputs.txt";
+const FAILED_RESPONSES_DIR: &str = "failed_responses";
+const DEFAULT_CONCURRENCY: usize = 10;
+const MAX_CONCURRENCY: usize = 500;
+const DEFAULT_QUOTA_LOW_REMAINING: u64 = 100;
+// the free Etherscan plan allows 5 calls per second
+const DEFAULT_ETHERSCAN_RPS: u32 = 5;
+// header names used by Ankr, the IETF RateLimit draft and other common gateways; the first present one wins
+const QUOTA_REMAINING_HEADERS: [&str; 4] = ["x-ratelimit-remaining", "ratelimit-remaining", "x-rate-limit-remaining", "x-ratelimit-remaining-requests"];
+const QUOTA_LIMIT_HEADERS: [&str; 4] = ["x-ratelimit-limit", "ratelimit-limit", "x-rate-limit-limit", "x-ratelimit-limit-requests"];
+const QUOTA_RESET_HEADERS: [&str; 4] = ["x-ratelimit-reset", "ratelimit-reset", "x-rate-limit-reset", "x-ratelimit-reset-requests"];
+const DEFAULT_CHAINS: &str = "eth,bsc,polygon,arbitrum,optimism,avalanche";
+const SUPPORTED_CHAINS: [ChainInfo; 19] = [
+    ChainInfo { id: "eth", name: "Ethereum", explorer: "https://etherscan.io" },
+    ChainInfo { id: "bsc", name: "BNB Chain", explorer: "https://bscscan.com" },
+    ChainInfo { id: "polygon", name: "Polygon", explorer: "https://polygonscan.com" },
+    ChainInfo { id: "arbitrum", name: "Arbitrum One", explorer: "https://arbiscan.io" },
+    ChainInfo { id: "optimism", name: "Optimism", explorer: "https://optimistic.etherscan.io" },
+    ChainInfo { id: "avalanche", name: "Avalanche C-Chain", explorer: "https://snowtrace.io" },
+    ChainInfo { id: "zksync", name: "zkSync Era", explorer: "https://explorer.zksync.io" },
+    ChainInfo { id: "base", name: "Base", explorer: "https://basescan.org" },
+    ChainInfo { id: "fantom", name: "Fantom", explorer: "https://ftmscan.com" },
+    ChainInfo { id: "gnosis", name: "Gnosis", explorer: "https://gnosisscan.io" },
+    ChainInfo { id: "linea", name: "Linea", explorer: "https://lineascan.build" },
+    ChainInfo { id: "scroll", name: "Scroll", explorer: "https://scrollscan.com" },
+    ChainInfo { id: "polygon_zkevm", name: "Polygon zkEVM", explorer: "https://zkevm.polygonscan.com" },
+    ChainInfo { id: "flare", name: "Flare", explorer: "https://flare-explorer.flare.network" },
+    ChainInfo { id: "syscoin", name: "Syscoin", explorer: "https://explorer.syscoin.org" },
+    ChainInfo { id: "rollux", name: "Rollux", explorer: "https://explorer.rollux.com" },
+    ChainInfo { id: "telos", name: "Telos", explorer: "https://teloscan.io" },
+    ChainInfo { id: "xai", name: "Xai", explorer: "https://explorer.xai-chain.net" },
+    ChainInfo { id: SOLANA_CHAIN, name: "Solana", explorer: "https://solscan.io" },
+];
+const TESTNET_CHAINS: [ChainInfo; 6] = [
+    ChainInfo { id: "eth_sepolia", name: "Sepolia", explorer: "https://sepolia.etherscan.io" },
+    ChainInfo { id: "eth_holesky", name: "Holesky", explorer: "https://holesky.etherscan.io" },
+    ChainInfo { id: "polygon_amoy", name: "Polygon Amoy", explorer: "https://amoy.polygonscan.com" },
+    ChainInfo { id: "avalanche_fuji", name: "Avalanche Fuji", explorer: "https://testnet.snowtrace.io" },
+    ChainInfo { id: "base_sepolia", name: "Base Sepolia", explorer: "https://sepolia.basescan.org" },
+    ChainInfo { id: "optimism_testnet", name: "Optimism Sepolia", explorer: "https://sepolia-optimism.etherscan.io" },
+];
+const TESTNET_IDS: [(&str, &str); 5] = [
+    ("eth", "eth_sepolia"),
+    ("polygon", "polygon_amoy"),
+    ("avalanche", "avalanche_fuji"),
+    ("base", "base_sepolia"),
+    ("optimism", "optimism_testnet"),
+];
+const CHAIN_ALIASES: [(&str, &str); 17] = [
+    ("ethereum", "eth"),
+    ("mainnet", "eth"),
+    ("binance", "bsc"),
+    ("bnb", "bsc"),
+    ("bsc_mainnet", "bsc"),
+    ("matic", "polygon"),
+    ("arb", "arbitrum"),
+    ("arbitrum_one", "arbitrum"),
+    ("op", "optimism"),
+    ("avax", "avalanche"),
+    ("avalanche_c", "avalanche"),
+    ("zksync_era", "zksync"),
+    ("era", "zksync"),
+    ("ftm", "fantom"),
+    ("xdai", "gnosis"),
+    ("zkevm", "polygon_zkevm"),
+    ("sol", SOLANA_CHAIN),
+];
+const PREFLIGHT_ADDRESS: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+const DEFAULT_QUERY_MODE: &str = "multi";
+const DEFAULT_RPC_PARAMS_TEMPLATE: &str = r#"{"blockchain": "{chain}", "address": "{address}"}"#;
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+const MAX_RETRIES: u32 = 5;
+// a malformed body rarely fixes itself; the one extra attempt covers a truncated response
+const PARSE_MAX_ATTEMPTS: u32 = 2;
+const DEFAULT_RETRY_DELAY_SECS: u64 = 10;
+const DEFAULT_CONFIRM_DELAY_SECS: u64 = 5;
+const DEFAULT_RPC_BATCH_SIZE: usize = 50;
+const DEFAULT_BATCH_SIZE: usize = 1000;
+const ACTIVITY_PAGE_SIZE: u32 = 100;
+const DEFAULT_ACTIVITY_MAX_PAGES: u32 = 10;
+const SHUTDOWN_GRACE_SECS: u64 = 10;
+const PROXY_FAILURE_THRESHOLD: u32 = 3;
+const PROXY_COOLDOWN_SECS: u64 = 60;
+const EXPLORERS_FILE: &str = "explorers.toml";
+const DEFAULT_FIXTURES_FILE: &str = "fixtures.json";
+const DEFAULT_MASK_PREFIX: usize = 4;
+const DEFAULT_MASK_SUFFIX: usize = 4;
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+// the Time cell of a row without a transaction; its Hash cell holds the status label (无交易, 网络错误, ...)
+pub const NO_TX_TIME: &str = "N/A";
+const ASCII_SYMBOLS: [(&str, &str); 6] = [("⚠️ ", "[WARN]"), ("⚠", "[WARN]"), ("✓", "[OK]"), ("✗", "[ERROR]"), ("○", "[--]"), ("🔑", "[KEY]")];
+
+static TIME_SETTINGS: OnceLock<TimeSettings> = OnceLock::new();
+static PLACEHOLDERS: OnceLock<Placeholders> = OnceLock::new();
+static EXPLORERS: OnceLock<ExplorerTemplates> = OnceLock::new();
+static MASKING: OnceLock<Masking> = OnceLock::new();
+static NEXT_REQUEST_ID: AtomicU32 = AtomicU32::new(1);
+static ASCII_OUTPUT: OnceLock<bool> = OnceLock::new();
+// with --quiet the console drops everything logged while this is set; the log file keeps it
+static CONSOLE_MUTED: AtomicBool = AtomicBool::new(false);
+
+struct ChainInfo {
+    id: &'static str,
+    name: &'static str,
+    explorer: &'static str,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TimeZoneMode {
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+impl TimeZoneMode {
+    fn label(self) -> &'static str {
+        match self {
+            TimeZoneMode::Local => "Local",
+            TimeZoneMode::Utc => "UTC",
+            TimeZoneMode::Named(tz) => tz.name(),
+        }
+    }
+}
+
+struct TimeSettings {
+    format: String,
+    zone: TimeZoneMode,
+}
+
+impl Default for TimeSettings {
+    fn default() -> Self {
+        TimeSettings {
+            format: DEFAULT_TIME_FORMAT.to_string(),
+            zone: TimeZoneMode::Local,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Network {
+    Mainnet,
+    Testnet,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputLayout {
+    ByChain,
+    ByAddress,
+    Flat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    Overwrite,
+    Append,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Xlsx,
+    CsvSplit,
+    Csv,
+    Jsonl,
+    GSheets,
+}
+
+impl OutputFormat {
+    fn is_streaming(self) -> bool {
+        matches!(self, OutputFormat::Csv | OutputFormat::Jsonl)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Xlsx => "xlsx",
+            OutputFormat::CsvSplit => "csv-split",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::GSheets => "gsheets",
+        }
+    }
+}
+
+// FILTER=active|inactive: which rows reach the output; the summary still counts everything
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ResultFilter {
+    Active,
+    Inactive,
+}
+
+impl ResultFilter {
+    fn keeps(self, row: &QueryResult) -> bool {
+        match self {
+            ResultFilter::Active => row.status == QueryStatus::Found,
+            ResultFilter::Inactive => row.status == QueryStatus::NoTransactions,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    Input,
+    Time,
+}
+
+const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("GIT_COMMIT"), " ", env!("BUILD_DATE"), ")");
+
+fn console_text(text: &str) -> String {
+    if !ASCII_OUTPUT.get().copied().unwrap_or(false) {
+        return text.to_string();
+    }
+    match ASCII_SYMBOLS.iter().find(|(symbol, _)| text.starts_with(symbol)) {
+        Some((symbol, ascii)) => format!("{}{}", ascii, &text[symbol.len()..]),
+        None => text.to_string(),
+    }
+}
+
+fn next_request_id() -> u32 {
+    NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// `spec` is the --chains value, which takes precedence over TARGET_CHAINS
+fn target_chains_spec(spec: Option<&str>) -> Option<String> {
+    spec.map(str::to_string).or_else(|| std::env::var("TARGET_CHAINS").ok())
+}
+
+fn load_target_chains(spec: Option<&str>) -> Vec<String> {
+    let (chains, duplicates) = dedup_chains(parse_target_chains(spec));
+    if !duplicates.is_empty() {
+        warn!("{}", tr!("chain.duplicates", duplicates.join(", ")));
+    }
+    chains
+}
+
+fn parse_target_chains(spec: Option<&str>) -> Vec<String> {
+    let chains_str = target_chains_spec(spec).unwrap_or_else(|| DEFAULT_CHAINS.to_string());
+    expand_target_chains(&chains_str)
+}
+
+// `all` expands to every supported mainnet chain, `-name` removes a chain wherever it appears in the list
+pub(crate) fn expand_target_chains(spec: &str) -> Vec<String> {
+    let mut chains = Vec::new();
+    let mut excluded = HashSet::new();
+    for part in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if let Some(name) = part.strip_prefix('-') {
+            excluded.insert(normalize_chain(name));
+        } else if part.eq_ignore_ascii_case(ALL_CHAINS) {
+            chains.extend(SUPPORTED_CHAINS.iter().map(|info| info.id.to_string()));
+        } else {
+            chains.push(normalize_chain(part));
+        }
+    }
+    chains.retain(|chain| !excluded.contains(chain));
+    chains
+}
+
+fn targets_all_chains(spec: &str) -> bool {
+    spec.split(',').any(|part| part.trim().eq_ignore_ascii_case(ALL_CHAINS))
+}
+
+fn dedup_chains(chains: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut seen = HashSet::new();
+    chains.into_iter().partition(|chain| seen.insert(chain.clone()))
+}
+
+pub fn normalize_chain(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    if let Some((_, id)) = CHAIN_ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        return id.to_string();
+    }
+    match SUPPORTED_CHAINS.iter().chain(TESTNET_CHAINS.iter()).find(|info| info.name.to_lowercase() == lower) {
+        Some(info) => info.id.to_string(),
+        None => lower,
+    }
+}
+
+fn chain_info(chain: &str) -> Option<&'static ChainInfo> {
+    SUPPORTED_CHAINS.iter().chain(TESTNET_CHAINS.iter()).find(|info| info.id == chain)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExplorerEntry {
+    Tx(String),
+    Templates { tx: Option<String>, address: Option<String> },
+}
+
+#[derive(Default)]
+struct ExplorerTemplates {
+    tx: std::collections::HashMap<String, String>,
+    address: std::collections::HashMap<String, String>,
+}
+
+impl ExplorerTemplates {
+    fn tx_url(&self, chain: &str, hash: &str) -> Option<String> {
+        match self.tx.get(chain) {
+            Some(template) => Some(template.replace("{hash}", hash)),
+            None => chain_info(chain).map(|info| format!("{}/tx/{}", info.explorer, hash)),
+        }
+    }
+
+    // address links are only written for chains that configure an address template
+    fn address_url(&self, chain: &str, address: &str) -> Option<String> {
+        self.address.get(chain).map(|template| template.replace("{address}", address))
+    }
+}
+
+fn load_explorers() -> Result<ExplorerTemplates> {
+    let configured = std::env::var("EXPLORERS_FILE").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let path = PathBuf::from(configured.as_deref().unwrap_or(EXPLORERS_FILE));
+    if configured.is_none() && !path.exists() {
+        return Ok(ExplorerTemplates::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| anyhow::anyhow!(tr!("explorers.read_failed", path.display(), e)))?;
+    let entries: std::collections::HashMap<String, ExplorerEntry> =
+        toml::from_str(&text).map_err(|e| anyhow::anyhow!(tr!("explorers.parse_failed", path.display(), e)))?;
+
+    let mut templates = ExplorerTemplates::default();
+    for (chain, entry) in entries {
+        let chain = normalize_chain(&chain);
+        let (tx, address) = match entry {
+            ExplorerEntry::Tx(tx) => (Some(tx), None),
+            ExplorerEntry::Templates { tx, address } => (tx, address),
+        };
+        for (template, placeholder, map) in [(tx, "{hash}", &mut templates.tx), (address, "{address}", &mut templates.address)] {
+            let Some(template) = template else {
+                continue;
+            };
+            if !template.contains(placeholder) {
+                anyhow::bail!(tr!("explorers.missing_placeholder", path.display(), chain, template, placeholder));
+            }
+            map.insert(chain.clone(), template);
+        }
+    }
+    info!("{}", tr!("explorers.loaded", path.display(), templates.tx.len(), templates.address.len()));
+    Ok(templates)
+}
+
+fn explorers() -> &'static ExplorerTemplates {
+    EXPLORERS.get_or_init(ExplorerTemplates::default)
+}
+
+fn load_network() -> Result<Network> {
+    match std::env::var("NETWORK").unwrap_or_default().trim().to_lowercase().as_str() {
+        "" | "mainnet" => Ok(Network::Mainnet),
+        "testnet" => Ok(Network::Testnet),
+        other => anyhow::bail!(tr!("config.invalid_network", other)),
+    }
+}
+
+fn is_testnet_chain(chain: &str) -> bool {
+    TESTNET_CHAINS.iter().any(|info| info.id == chain)
+}
+
+fn apply_network(chains: Vec<String>, network: Network, spec: Option<&str>) -> Result<Vec<String>> {
+    match network {
+        Network::Mainnet => {
+            let testnets: Vec<&String> = chains.iter().filter(|c| is_testnet_chain(c)).collect();
+            if !testnets.is_empty() && testnets.len() < chains.len() {
+                anyhow::bail!(tr!(
+                    "chain.mixed_networks",
+                    testnets.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+                ));
+            }
+            Ok(chains)
+        }
+        Network::Testnet => {
+            // chains named one by one must have a testnet; with `all` the ones without are simply left out
+            let explicit = target_chains_spec(spec).is_some_and(|spec| !targets_all_chains(&spec));
+            let mut mapped = Vec::new();
+            for chain in chains {
+                if is_testnet_chain(&chain) {
+                    mapped.push(chain);
+                } else if let Some((_, testnet)) = TESTNET_IDS.iter().find(|(mainnet, _)| *mainnet == chain) {
+                    mapped.push(testnet.to_string());
+                } else if explicit {
+                    anyhow::bail!(tr!(
+                        "chain.no_testnet",
+                        chain,
+                        TESTNET_IDS.iter().map(|(mainnet, testnet)| format!("{} → {}", mainnet, testnet)).collect::<Vec<_>>().join(", ")
+                    ));
+                }
+            }
+            let (mapped, duplicates) = dedup_chains(mapped);
+            if !duplicates.is_empty() {
+                warn!("{}", tr!("chain.duplicates", duplicates.join(", ")));
+            }
+            Ok(mapped)
+        }
+    }
+}
+
+pub fn chain_display_name(chain: &str) -> &str {
+    chain_info(chain).map(|info| info.name).unwrap_or(chain)
+}
+
+// hash prefix for log lines; counts chars so a short or non-ASCII hash from a bad response cannot panic
+pub(crate) fn short_hash(hash: &str) -> String {
+    hash.chars().take(12).collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr.push((prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+fn validate_target_chains(chains: &[String]) -> Result<()> {
+    let strict = std::env::var("STRICT_CHAINS").map(|v| v == "1").unwrap_or(false);
+    let unknown: Vec<&String> = chains.iter().filter(|c| chain_info(c).is_none()).collect();
+    let supported: Vec<&str> = SUPPORTED_CHAINS.iter().map(|info| info.id).collect();
+
+    for chain in &unknown {
+        let closest = supported
+            .iter()
+            .chain(CHAIN_ALIASES.iter().map(|(alias, _)| alias))
+            .map(|candidate| (edit_distance(chain, candidate), *candidate))
+            .min()
+            .filter(|(distance, _)| *distance <= chain.len().div_ceil(2).max(2));
+        match closest {
+            Some((_, suggestion)) => warn!("{}", tr!("chain.unknown_suggest", chain, suggestion)),
+            None => warn!("{}", tr!("chain.unknown", chain)),
+        }
+    }
+
+    if !unknown.is_empty() {
+        if strict {
+            anyhow::bail!(tr!("chain.strict_unsupported", unknown.len(), supported.join(", ")));
+        }
+        warn!("{}", tr!("chain.supported", supported.join(", ")));
+    }
+    Ok(())
+}
+
+fn load_query_mode() -> String {
+    std::env::var("QUERY_MODE").unwrap_or_else(|_| DEFAULT_QUERY_MODE.to_string()).to_lowercase()
+}
+
+// 0 would leave the semaphore without permits and hang the run, so it is an error rather than a fallback
+pub(crate) fn parse_concurrency(value: Option<&str>) -> Result<usize> {
+    let Some(raw) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(DEFAULT_CONCURRENCY);
+    };
+    match raw.parse::<usize>() {
+        Ok(0) => anyhow::bail!(t("config.concurrency_zero")),
+        Ok(n) if n > MAX_CONCURRENCY => {
+            warn!("{}", tr!("config.concurrency_clamped", n, MAX_CONCURRENCY));
+            Ok(MAX_CONCURRENCY)
+        }
+        Ok(n) => Ok(n),
+        Err(_) => {
+            warn!("{}", tr!("config.concurrency_invalid", raw, DEFAULT_CONCURRENCY));
+            Ok(DEFAULT_CONCURRENCY)
+        }
+    }
+}
+
+fn load_adaptive_bounds(concurrency: usize) -> Option<(usize, usize)> {
+    if std::env::var("ADAPTIVE_CONCURRENCY").map(|v| v != "1").unwrap_or(true) {
+        return None;
+    }
+    let read = |key: &str, default: usize| std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default);
+    let min = read("CONCURRENCY_MIN", 1).max(1);
+    let max = read("CONCURRENCY_MAX", concurrency * 4).clamp(concurrency, MAX_CONCURRENCY.max(concurrency));
+    Some((min.min(concurrency), max))
+}
+
+// CONCURRENCY_<CHAIN> (e.g. CONCURRENCY_ETH=5) caps the requests in flight for that chain on top of CONCURRENCY
+fn load_chain_concurrency() -> std::collections::HashMap<String, usize> {
+    SUPPORTED_CHAINS
+        .iter()
+        .chain(TESTNET_CHAINS.iter())
+        .filter_map(|info| {
+            let limit = std::env::var(format!("CONCURRENCY_{}", info.id.to_uppercase())).ok()?.trim().parse::<usize>().ok()?;
+            Some((info.id.to_string(), limit.max(1)))
+        })
+        .collect()
+}
+
+fn load_rate_limit_rps() -> Option<NonZeroU32> {
+    std::env::var("RATE_LIMIT_RPS").ok().and_then(|v| v.trim().parse().ok())
+}
+
+fn load_rpc_batch_size() -> usize {
+    std::env::var("RPC_BATCH_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_RPC_BATCH_SIZE)
+        .max(1)
+}
+
+fn load_sort_order() -> SortOrder {
+    match std::env::var("SORT_BY").unwrap_or_default().to_lowercase().as_str() {
+        "time" => SortOrder::Time,
+        _ => SortOrder::Input,
+    }
+}
+
+fn load_result_filter() -> Option<ResultFilter> {
+    match std::env::var("FILTER").unwrap_or_default().trim().to_lowercase().as_str() {
+        "active" => Some(ResultFilter::Active),
+        "inactive" => Some(ResultFilter::Inactive),
+        "" | "all" => None,
+        other => {
+            warn!("{}", tr!("config.invalid_filter", other));
+            None
+        }
+    }
+}
+
+fn load_fail_threshold() -> Option<f64> {
+    let threshold = std::env::var("FAIL_THRESHOLD").ok().and_then(|v| v.trim().parse::<f64>().ok()).filter(|t| (0.0..=1.0).contains(t));
+    let fail_on_error = std::env::var("FAIL_ON_ERROR").map(|v| v == "1").unwrap_or(false);
+    threshold.or(fail_on_error.then_some(0.0))
+}
+
+// caps the wall time of the whole run; on expiry the run stops like on Ctrl-C
+fn load_max_runtime() -> Option<Duration> {
+    std::env::var("MAX_RUNTIME_SECS").ok().and_then(|v| v.trim().parse::<u64>().ok()).filter(|&secs| secs > 0).map(Duration::from_secs)
+}
+
+// EMPTY_RECHECK (on/off) and EMPTY_RECHECK_DELAY_SECS take precedence over CONFIRM_EMPTY / CONFIRM_DELAY_SECS
+fn load_confirm_empty_delay() -> Option<Duration> {
+    let enabled = match std::env::var("EMPTY_RECHECK").map(|v| v.trim().to_lowercase()) {
+        Ok(v) if matches!(v.as_str(), "on" | "1" | "true") => true,
+        Ok(v) if matches!(v.as_str(), "off" | "0" | "false") => false,
+        _ => std::env::var("CONFIRM_EMPTY").map(|v| v == "1").unwrap_or(false),
+    };
+    if !enabled {
+        return None;
+    }
+    let secs = ["EMPTY_RECHECK_DELAY_SECS", "CONFIRM_DELAY_SECS"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().and_then(|v| v.trim().parse().ok()))
+        .unwrap_or(DEFAULT_CONFIRM_DELAY_SECS);
+    Some(Duration::from_secs(secs))
+}
+
+fn load_inactive_days() -> Option<i64> {
+    std::env::var("INACTIVE_DAYS").ok().and_then(|v| v.trim().parse().ok())
+}
+
+fn load_time_settings() -> Result<TimeSettings> {
+    let format = std::env::var("TIME_FORMAT").unwrap_or_else(|_| DEFAULT_TIME_FORMAT.to_string());
+    let zone_str = std::env::var("TIMEZONE").or_else(|_| std::env::var("TIME_ZONE")).unwrap_or_default();
+    let zone = match zone_str.trim().to_lowercase().as_str() {
+        "" | "local" => TimeZoneMode::Local,
+        "utc" => TimeZoneMode::Utc,
+        _ => match zone_str.trim().parse::<Tz>() {
+            Ok(tz) => TimeZoneMode::Named(tz),
+            Err(_) => return Err(anyhow::anyhow!(tr!("config.invalid_timezone", zone_str))),
+        },
+    };
+
+    let mut sample = String::new();
+    if std::fmt::write(&mut sample, format_args!("{}", Utc::now().format(&format))).is_err() {
+        return Err(anyhow::anyhow!(tr!("config.invalid_time_format", format)));
+    }
+    Ok(TimeSettings { format, zone })
+}
+
+// EMPTY_HASH_TEXT / EMPTY_TIME_TEXT replace the Hash and Time cells of rows without a transaction in every output
+// except JSON/JSONL, which keep the status field. Set but empty means a blank cell
+#[derive(Clone, Debug, Default)]
+pub struct Placeholders {
+    pub hash: Option<String>,
+    pub time: Option<String>,
+}
+
+impl Placeholders {
+    pub fn from_env() -> Self {
+        let read = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string());
+        Placeholders { hash: read("EMPTY_HASH_TEXT"), time: read("EMPTY_TIME_TEXT") }
+    }
+
+    pub fn hash<'a>(&'a self, row: &'a QueryResult) -> &'a str {
+        match &self.hash {
+            Some(text) if row.status != QueryStatus::Found => text,
+            _ => &row.tx_hash,
+        }
+    }
+
+    pub fn time<'a>(&'a self, row: &'a QueryResult) -> &'a str {
+        match &self.time {
+            Some(text) if row.status != QueryStatus::Found => text,
+            _ => &row.tx_time,
+        }
+    }
+}
+
+fn placeholders() -> &'static Placeholders {
+    PLACEHOLDERS.get_or_init(Placeholders::default)
+}
+
+fn load_proxy_url() -> Option<String> {
+    let read = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    if let Some(url) = read("SOCKS_PROXY") {
+        return Some(if url.contains("://") { url } else { format!("socks5h://{}", url) });
+    }
+    read("HTTP_PROXY").map(|url| if url.contains("://") { url } else { format!("http://{}", url) })
+}
+
+fn load_proxy_urls() -> Vec<String> {
+    let pool: Vec<String> = std::env::var("PROXY_POOL")
+        .unwrap_or_default()
+        .split(',')
+        .map(|url| url.trim())
+        .filter(|url| !url.is_empty())
+        .map(|url| if url.contains("://") { url.to_string() } else { format!("http://{}", url) })
+        .collect();
+    if pool.is_empty() {
+        load_proxy_url().into_iter().collect()
+    } else {
+        pool
+    }
+}
+
+fn mask_proxy_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[derive(Default)]
+pub struct ClientSettings {
+    pub user_agent: Option<String>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+    pub http2_only: bool,
+}
+
+pub fn load_client_settings() -> ClientSettings {
+    ClientSettings {
+        user_agent: std::env::var("USER_AGENT").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+        pool_max_idle_per_host: std::env::var("POOL_MAX_IDLE_PER_HOST").ok().and_then(|v| v.trim().parse().ok()),
+        tcp_keepalive: std::env::var("TCP_KEEPALIVE_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs),
+        http2_only: std::env::var("HTTP2_ONLY").map(|v| v == "1").unwrap_or(false),
+    }
+}
+
+fn build_client(proxy_url: Option<&str>, settings: &ClientSettings) -> Result<Client> {
+    let mut builder = Client::builder();
+    if let Some(user_agent) = &settings.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(max_idle) = settings.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(keepalive) = settings.tcp_keepalive {
+        builder = builder.tcp_keepalive(keepalive);
+    }
+    if settings.http2_only {
+        builder = builder.http2_prior_knowledge();
+    }
+    if let Some(url) = proxy_url {
+        let parsed = reqwest::Url::parse(url).map_err(|e| anyhow::anyhow!(tr!("proxy.invalid", mask_proxy_url(url), e)))?;
+        if !["http", "https", "socks5", "socks5h"].contains(&parsed.scheme()) || parsed.host_str().is_none() {
+            anyhow::bail!(tr!("proxy.invalid_scheme", mask_proxy_url(url)));
+        }
+        let proxy = reqwest::Proxy::all(url).map_err(|e| anyhow::anyhow!(tr!("proxy.invalid", mask_proxy_url(url), e)))?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+fn load_output_layout() -> OutputLayout {
+    match std::env::var("OUTPUT_LAYOUT").unwrap_or_default().to_lowercase().as_str() {
+        "by_address" => OutputLayout::ByAddress,
+        "flat" => OutputLayout::Flat,
+        _ => OutputLayout::ByChain,
+    }
+}
+
+fn load_output_mode() -> OutputMode {
+    match std::env::var("OUTPUT_MODE").unwrap_or_default().to_lowercase().as_str() {
+        "append" => OutputMode::Append,
+        _ => OutputMode::Overwrite,
+    }
+}
+
+fn load_output_formats() -> Vec<OutputFormat> {
+    let formats: Vec<OutputFormat> = std::env::var("OUTPUT_FORMAT")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|f| match f.trim().to_lowercase().as_str() {
+            "xlsx" => Some(OutputFormat::Xlsx),
+            "csv-split" | "csv_split" => Some(OutputFormat::CsvSplit),
+            "csv" => Some(OutputFormat::Csv),
+            "jsonl" => Some(OutputFormat::Jsonl),
+            "gsheets" => Some(OutputFormat::GSheets),
+            _ => None,
+        })
+        .collect();
+    if formats.is_empty() {
+        vec![OutputFormat::Xlsx]
+    } else {
+        formats
+    }
+}
+
+fn load_output_dir() -> PathBuf {
+    std::env::var("OUTPUT_DIR").ok().filter(|v| !v.trim().is_empty()).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(DEFAULT_OUTPUT_DIR))
+}
+
+#[derive(Serialize)]
+struct RpcRequestSingle<'a> {
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: RpcParamsSingle<'a>,
+    id: u32,
+}
+
+#[derive(Serialize)]
+struct RpcRequestMulti<'a> {
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: RpcParamsMulti<'a>,
+    id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcParamsSingle<'a> {
+    blockchain: &'a str,
+    address: &'a str,
+    desc_order: bool,
+    page_size: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcParamsMulti<'a> {
+    blockchain: Vec<&'a str>,
+    address: &'a str,
+    desc_order: bool,
+    page_size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RpcRequestCustom<'a> {
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: serde_json::Value,
+    id: u32,
+}
+
+#[derive(Serialize)]
+struct RpcRequestGetCode<'a> {
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: (&'a str, &'a str),
+    id: u32,
+}
+
+#[derive(Serialize)]
+struct SolanaRequest<'a> {
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: (&'a str, SolanaParams),
+    id: u32,
+}
+
+#[derive(Serialize)]
+struct SolanaParams {
+    limit: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SolanaSignature {
+    signature: String,
+    block_time: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RpcResponse<T = RpcResult> {
+    id: Option<u32>,
+    result: Option<T>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RpcError {
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct RpcResult {
+    next_page_token: Option<String>,
+    transactions: Vec<Transaction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Transaction {
+    hash: String,
+    timestamp: String,
+    #[serde(default)]
+    blockchain: String,
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    value: Option<String>,
+}
+
+
+#[derive(Deserialize, Debug)]
+struct TokenTransfersResult {
+    #[serde(default)]
+    transfers: Vec<TokenTransfer>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TokenTransfer {
+    transaction_hash: String,
+    // unix seconds, unlike the hex timestamps of ankr_getTransactionsByAddress
+    timestamp: Option<i64>,
+    #[serde(default)]
+    from_address: Option<String>,
+    #[serde(default)]
+    to_address: Option<String>,
+    #[serde(default)]
+    contract_address: Option<String>,
+    // already scaled by the token decimals
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    token_symbol: Option<String>,
+}
+
+pub fn identify_input(input: &str) -> (&str, bool) {
+    let trimmed = input.trim();
+
+    if trimmed.starts_with("0x") && trimmed.len() == 42 && trimmed[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+        return (trimmed, false);
+    }
+
+    if !trimmed.starts_with("0x") && trimmed.len() == 40 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
+        return (trimmed, false);
+    }
+
+    if trimmed.starts_with("0x") && trimmed.len() == 66 && trimmed[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+        return (trimmed, true);
+    }
+
+    if !trimmed.starts_with("0x") && trimmed.len() == 64 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
+        return (trimmed, true);
+    }
+
+    (trimmed, false)
+}
+
+// how many characters of a private key (or an unrecognized input line) are shown in logs, not counting 0x
+#[derive(Clone, Copy, Debug)]
+pub struct Masking {
+    pub prefix: usize,
+    pub suffix: usize,
+}
+
+impl Default for Masking {
+    fn default() -> Self {
+        Masking { prefix: DEFAULT_MASK_PREFIX, suffix: DEFAULT_MASK_SUFFIX }
+    }
+}
+
+impl Masking {
+    pub fn mask(&self, value: &str) -> String {
+        let body = value.strip_prefix("0x").unwrap_or(value);
+        let chars: Vec<char> = body.chars().collect();
+        let shown = self.prefix + self.suffix;
+        // never show more than stays hidden, so short values are masked entirely
+        if shown == 0 || chars.len() < shown * 2 {
+            return "***".to_string();
+        }
+        let head: String = chars[..self.prefix].iter().collect();
+        let tail: String = chars[chars.len() - self.suffix..].iter().collect();
+        format!("{}{}...{}", &value[..value.len() - body.len()], head, tail)
+    }
+}
+
+fn load_masking() -> Masking {
+    if std::env::var("MASK_FULL").map(|v| v == "1").unwrap_or(false) {
+        return Masking { prefix: 0, suffix: 0 };
+    }
+    let load = |key: &str, default: usize| std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default);
+    Masking { prefix: load("MASK_PREFIX", DEFAULT_MASK_PREFIX), suffix: load("MASK_SUFFIX", DEFAULT_MASK_SUFFIX) }
+}
+
+pub fn mask_private_key(pk: &str) -> String {
+    MASKING.get_or_init(load_masking).mask(pk)
+}
+
+pub fn private_key_to_address(private_key: &str) -> Option<String> {
+    let pk = if private_key.starts_with("0x") {
+        private_key
+    } else {
+        &format!("0x{}", private_key)
+    };
+
+    match ethers::signers::LocalWallet::from_str(pk) {
+        Ok(wallet) => Some(ethers::utils::to_checksum(&wallet.address(), None)),
+        Err(_) => None,
+    }
+}
+
+struct WalletRow {
+    line: u64,
+    value: String,
+    label: Option<String>,
+}
+
+pub struct Wallet {
+    pub address: String,
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WalletEntry {
+    Plain(String),
+    Labeled {
+        address: String,
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+fn load_csv_has_header() -> bool {
+    std::env::var("CSV_HAS_HEADER")
+        .map(|v| !matches!(v.trim().to_lowercase().as_str(), "0" | "false" | "no"))
+        .unwrap_or(true)
+}
+
+fn load_csv_column(key: &str, headers: Option<&StringRecord>) -> Result<Option<usize>> {
+    let Ok(value) = std::env::var(key) else {
+        return Ok(None);
+    };
+    let value = value.trim();
+
+    if let Ok(index) = value.parse::<usize>() {
+        if let Some(headers) = headers.filter(|h| index >= h.len()) {
+            anyhow::bail!(tr!("csv.column_out_of_range", key, index, headers.len()));
+        }
+        return Ok(Some(index));
+    }
+
+    let Some(headers) = headers else {
+        anyhow::bail!(tr!("csv.column_name_without_header", key, value));
+    };
+    match headers.iter().position(|h| h.trim().eq_ignore_ascii_case(value)) {
+        Some(index) => Ok(Some(index)),
+        None => anyhow::bail!(tr!("csv.column_missing", key, value, headers.iter().collect::<Vec<_>>().join(", "))),
+    }
+}
+
+const CSV_DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+fn sniff_csv_delimiter(text: &str) -> u8 {
+    let Some(line) = text.lines().map(|l| l.trim_start_matches('\u{feff}')).find(|l| !is_skipped_line(l)) else {
+        return b',';
+    };
+    let mut counts = [0usize; CSV_DELIMITERS.len()];
+    let mut quoted = false;
+    for byte in line.bytes() {
+        match byte {
+            b'"' => quoted = !quoted,
+            _ if quoted => {}
+            _ => {
+                if let Some(i) = CSV_DELIMITERS.iter().position(|&d| d == byte) {
+                    counts[i] += 1;
+                }
+            }
+        }
+    }
+    // ties (including no delimiter at all) keep the comma, which comes first
+    let best = (0..counts.len()).rev().max_by_key(|&i| counts[i]).unwrap_or(0);
+    CSV_DELIMITERS[best]
+}
+
+fn load_csv_delimiter(text: &str) -> u8 {
+    let configured = std::env::var("CSV_DELIMITER").ok().and_then(|v| match v.to_lowercase().as_str() {
+        "tab" | "\\t" | "\t" => Some(b'\t'),
+        "comma" => Some(b','),
+        "semicolon" => Some(b';'),
+        "pipe" => Some(b'|'),
+        other if other.len() == 1 => Some(other.as_bytes()[0]),
+        _ => None,
+    });
+    configured.unwrap_or_else(|| sniff_csv_delimiter(text))
+}
+
+fn delimiter_name(delimiter: u8) -> String {
+    match delimiter {
+        b'\t' => "tab".to_string(),
+        other => (other as char).to_string(),
+    }
+}
+
+fn read_csv_rows(file: File) -> Result<Vec<WalletRow>> {
+    let text = io::read_to_string(file)?;
+    let delimiter = load_csv_delimiter(&text);
+    if delimiter != b',' {
+        info!("{}", tr!("csv.delimiter", delimiter_name(delimiter)));
+    }
+    let has_header = load_csv_has_header();
+    let mut rdr = ReaderBuilder::new().has_headers(has_header).flexible(true).delimiter(delimiter).from_reader(text.as_bytes());
+    let headers = if has_header { Some(rdr.headers()?.clone()) } else { None };
+    let address_col = load_csv_column("CSV_ADDRESS_COLUMN", headers.as_ref())?.unwrap_or(0);
+    let label_col = match load_csv_column("CSV_LABEL_COLUMN", headers.as_ref())? {
+        Some(col) => Some(col),
+        None => headers.as_ref().and_then(|h| {
+            h.iter().position(|name| ["label", "tag", "备注"].contains(&name.trim().to_lowercase().as_str()))
+        }),
+    };
+
+    let mut rows = Vec::new();
+    for result in rdr.records() {
+        let record = result?;
+        let line = record.position().map(|pos| pos.line()).unwrap_or_default();
+        if let Some(comment) = record.get(0).filter(|first| is_skipped_line(first)) {
+            rows.push(WalletRow { line, value: comment.to_string(), label: None });
+            continue;
+        }
+        let Some(field) = record.get(address_col) else {
+            anyhow::bail!(tr!("csv.row_missing_column", line, address_col));
+        };
+        let label = label_col.and_then(|col| record.get(col)).map(str::trim).filter(|l| !l.is_empty());
+        rows.push(WalletRow { line, value: field.to_string(), label: label.map(str::to_string) });
+    }
+    Ok(rows)
+}
+
+// Excel turns long digit strings into floats; print them back without an exponent so they at least show up as invalid lines
+fn xlsx_cell_text(cell: &calamine::Data) -> String {
+    match cell {
+        calamine::Data::Float(n) if n.fract() == 0.0 => format!("{:.0}", n),
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+// XLSX_SHEET picks the sheet; otherwise the first one that is not the summary sheet of our own output.
+// Columns follow CSV_ADDRESS_COLUMN / CSV_LABEL_COLUMN / CSV_HAS_HEADER, and our own address/label headers are recognized
+fn read_xlsx_rows(path: &Path) -> Result<Vec<WalletRow>> {
+    let mut workbook = calamine::open_workbook_auto(path).map_err(|e| anyhow::anyhow!(tr!("input.open_failed", path.display(), e)))?;
+    let sheets = workbook.sheet_names();
+    let sheet = match std::env::var("XLSX_SHEET").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+        Some(name) if sheets.contains(&name) => name,
+        Some(name) => anyhow::bail!(tr!("xlsx.sheet_missing", name, path.display(), sheets.join(", "))),
+        None => match sheets.iter().find(|name| !i18n::t_all("sheet.summary").contains(&name.as_str())) {
+            Some(name) => name.clone(),
+            None => return Ok(Vec::new()),
+        },
+    };
+    let range = workbook.worksheet_range(&sheet)?;
+    let mut cells = range.rows().map(|row| row.iter().map(xlsx_cell_text).collect::<Vec<_>>());
+
+    let headers = if load_csv_has_header() { cells.next().map(StringRecord::from) } else { None };
+    let named = |keys: &[&'static str], names: &[&str]| {
+        headers.as_ref().and_then(|h| {
+            h.iter().position(|name| {
+                let name = name.trim();
+                names.contains(&name.to_lowercase().as_str()) || keys.iter().any(|key| i18n::t_all(key).contains(&name))
+            })
+        })
+    };
+    let address_col = match load_csv_column("CSV_ADDRESS_COLUMN", headers.as_ref())? {
+        Some(col) => col,
+        None => named(&["header.wallet_address", "header.address"], &["address", "wallet"]).unwrap_or(0),
+    };
+    let label_col = match load_csv_column("CSV_LABEL_COLUMN", headers.as_ref())? {
+        Some(col) => Some(col),
+        None => named(&["header.label"], &["label", "tag", "备注"]),
+    };
+
+    let first_line = if headers.is_some() { 2 } else { 1 };
+    Ok(cells
+        .enumerate()
+        .map(|(i, row)| {
+            let value = row.get(address_col).cloned().unwrap_or_default();
+            let label = label_col.and_then(|col| row.get(col)).filter(|l| !l.is_empty()).cloned();
+            WalletRow { line: i as u64 + first_line, value, label }
+        })
+        .collect())
+}
+
+fn read_txt_rows(reader: impl BufRead) -> Vec<WalletRow> {
+    reader
+        .lines()
+        .map_while(|line| line.ok())
+        .enumerate()
+        .map(|(i, value)| {
+            let value = if i == 0 { value.trim_start_matches('\u{feff}').to_string() } else { value };
+            WalletRow { line: i as u64 + 1, value, label: None }
+        })
+        .collect()
+}
+
+fn is_skipped_line(value: &str) -> bool {
+    let trimmed = value.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn entry_rows(entries: Vec<WalletEntry>) -> Vec<WalletRow> {
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (value, label) = match entry {
+                WalletEntry::Plain(value) => (value, None),
+                WalletEntry::Labeled { address, label } => (address, label.filter(|l| !l.trim().is_empty())),
+            };
+            WalletRow { line: i as u64 + 1, value, label }
+        })
+        .collect()
+}
+
+fn read_wallet_file(path: &Path) -> Result<Vec<WalletRow>> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    if matches!(extension.as_str(), "xlsx" | "xls" | "ods") {
+        return read_xlsx_rows(path);
+    }
+    let file = File::open(path).map_err(|e| anyhow::anyhow!(tr!("input.open_failed", path.display(), e)))?;
+    match extension.as_str() {
+        "csv" => read_csv_rows(file),
+        "json" => Ok(entry_rows(serde_json::from_reader(io::BufReader::new(file))?)),
+        "yaml" | "yml" => Ok(entry_rows(serde_yaml::from_reader(io::BufReader::new(file))?)),
+        _ => Ok(read_txt_rows(io::BufReader::new(file))),
+    }
+}
+
+fn read_wallet_rows(input: Option<&Path>) -> Result<(String, Vec<WalletRow>)> {
+    let (source, rows) = read_raw_wallet_rows(input)?;
+    let total = rows.len();
+    let rows: Vec<WalletRow> = rows.into_iter().filter(|row| !is_skipped_line(&row.value)).collect();
+    if rows.len() < total {
+        info!("{}", tr!("input.skipped_lines", total - rows.len()));
+    }
+    Ok((source, rows))
+}
+
+fn is_glob_pattern(value: &str) -> bool {
+    value.contains(['*', '?', '['])
+}
+
+fn load_input_files(input: Option<&Path>) -> Result<Option<Vec<PathBuf>>> {
+    let spec = match input {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => std::env::var("INPUT_FILES").unwrap_or_default(),
+    };
+    if !spec.contains(',') && !is_glob_pattern(&spec) && (input.is_some() || spec.trim().is_empty()) {
+        return Ok(None);
+    }
+
+    let mut paths = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        if !is_glob_pattern(part) {
+            paths.push(PathBuf::from(part));
+            continue;
+        }
+        let matches = glob::glob(part).map_err(|e| anyhow::anyhow!(tr!("input.invalid_glob", part, e)))?;
+        let mut matched: Vec<PathBuf> = matches.filter_map(|m| m.ok()).filter(|p| p.is_file()).collect();
+        if matched.is_empty() {
+            anyhow::bail!(tr!("input.glob_no_match", part));
+        }
+        matched.sort();
+        paths.append(&mut matched);
+    }
+    let mut seen = HashSet::new();
+    paths.retain(|p| seen.insert(p.clone()));
+    Ok(Some(paths))
+}
+
+fn read_raw_wallet_rows(input: Option<&Path>) -> Result<(String, Vec<WalletRow>)> {
+    let stdin_input = std::env::var("STDIN_INPUT").map(|v| v == "1").unwrap_or(false);
+    if stdin_input || input.is_some_and(|path| path.as_os_str() == "-") {
+        return Ok((t("input.stdin").to_string(), read_txt_rows(io::stdin().lock())));
+    }
+
+    if let Some(paths) = load_input_files(input)? {
+        let mut rows = Vec::new();
+        for path in &paths {
+            let file_rows = read_wallet_file(path)?;
+            let count = file_rows.iter().filter(|row| !is_skipped_line(&row.value)).count();
+            info!("{}", tr!("input.file_rows", path.display(), count));
+            rows.extend(file_rows);
+        }
+        let source = paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ");
+        return Ok((source, rows));
+    }
+
+    if let Some(path) = input {
+        return Ok((path.display().to_string(), read_wallet_file(path)?));
+    }
+
+    for candidate in [WALLET_FILE, WALLET_JSON_FILE, WALLET_YAML_FILE, WALLET_TXT_FILE] {
+        if Path::new(candidate).exists() {
+            return Ok((candidate.to_string(), read_wallet_file(Path::new(candidate))?));
+        }
+    }
+
+    Err(anyhow::anyhow!(tr!(
+        "input.not_found",
+        WALLET_FILE,
+        WALLET_JSON_FILE,
+        WALLET_YAML_FILE,
+        WALLET_TXT_FILE
+    )))
+}
+
+// `chains` are the target chains; Solana addresses are only recognised when solana is one of them
+pub fn load_wallet_addresses(input: Option<&Path>, chains: &[String]) -> Result<Vec<Wallet>> {
+    let (source, rows) = read_wallet_rows(input)?;
+    let mut addresses = Vec::new();
+    let mut invalid = Vec::new();
+    let mut derived = 0;
+    let solana = chains.iter().any(|chain| chain == SOLANA_CHAIN);
+    let log_key_map = load_log_key_map();
+
+    for row in &rows {
+        let (normalized, is_private_key) = identify_input(&row.value);
+
+        if is_private_key {
+            if let Some(address) = private_key_to_address(normalized) {
+                if log_key_map {
+                    info!("{}", tr!("input.private_key_derived", mask_private_key(normalized), address));
+                }
+                derived += 1;
+                addresses.push(Wallet { address, label: row.label.clone() });
+            } else {
+                warn!("{}", tr!("input.private_key_invalid", mask_private_key(&row.value)));
+                invalid.push(tr!("input.invalid_private_key_line", row.line, mask_private_key(normalized)));
+            }
+        } else if let Some(address) = normalize_address(normalized) {
+            if checksum_mismatch(normalized, &address) {
+                warn!("{}", tr!("input.checksum_mismatch", row.line, normalized, address));
+            }
+            addresses.push(Wallet { address, label: row.label.clone() });
+        // checked after the EVM form: 40 hex digits without a 0 are valid base58 as well
+        } else if solana && is_solana_address(normalized) {
+            addresses.push(Wallet { address: normalized.to_string(), label: row.label.clone() });
+        } else if let Some(problem) = address_problem(normalized) {
+            warn!("{}", tr!("input.malformed_address", row.line, problem, normalized));
+            invalid.push(tr!("input.malformed_line", row.line, normalized, problem));
+        } else {
+            invalid.push(tr!("input.unrecognized_line", row.line, mask_private_key(normalized)));
+        }
+    }
+    if derived > 0 && !log_key_map {
+        info!("{}", tr!("input.private_keys_derived", derived));
+    }
+
+    if invalid.is_empty() {
+        let _ = std::fs::remove_file(INVALID_INPUTS_FILE);
+    } else {
+        std::fs::write(INVALID_INPUTS_FILE, invalid.join("\n") + "\n")?;
+        warn!("{}", tr!("input.invalid_written", invalid.len(), INVALID_INPUTS_FILE));
+    }
+
+    if let Some(filter) = load_address_filter()? {
+        let before = addresses.len();
+        addresses.retain(|w| filter.is_match(&w.address));
+        info!("{}", tr!("input.filtered", filter.as_str(), addresses.len(), before - addresses.len()));
+    }
+
+    let mut seen = HashSet::new();
+    let loaded = addresses.len();
+    addresses.retain(|w| seen.insert(w.address.clone()));
+    if addresses.len() < loaded {
+        warn!("{}", tr!("input.duplicates", loaded - addresses.len()));
+    }
+
+    info!("{}", tr!("input.loaded", source, addresses.len()));
+    Ok(addresses)
+}
+
+// a masked key next to its address still ties the two together in shared logs
+fn load_log_key_map() -> bool {
+    std::env::var("LOG_KEY_MAP").map(|v| v == "1").unwrap_or(false)
+}
+
+fn load_address_filter() -> Result<Option<Regex>> {
+    match std::env::var("ADDRESS_FILTER") {
+        Ok(pattern) if !pattern.trim().is_empty() => {
+            let regex = Regex::new(pattern.trim()).map_err(|e| anyhow::anyhow!(tr!("config.invalid_address_filter", pattern.trim(), e)))?;
+            Ok(Some(regex))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn load_preflight() -> bool {
+    std::env::var("PREFLIGHT").map(|v| v.trim() != "0").unwrap_or(true)
+}
+
+fn load_limit(cli_value: Option<usize>) -> Option<usize> {
+    cli_value.or_else(|| std::env::var("LIMIT").ok().and_then(|v| v.trim().parse().ok())).filter(|&n| n > 0)
+}
+
+fn load_offset(cli_value: Option<usize>) -> usize {
+    cli_value.or_else(|| std::env::var("OFFSET").ok().and_then(|v| v.trim().parse().ok())).unwrap_or(0)
+}
+
+fn apply_limit(wallets: Vec<Wallet>, offset: usize, limit: Option<usize>) -> Vec<Wallet> {
+    if offset == 0 && limit.is_none() {
+        return wallets;
+    }
+    let total = wallets.len();
+    let selected: Vec<Wallet> = wallets.into_iter().skip(offset).take(limit.unwrap_or(usize::MAX)).collect();
+    if selected.is_empty() {
+        warn!("{}", tr!("input.limit_empty", offset, total));
+    } else {
+        info!("{}", tr!("input.limited", offset + 1, offset + selected.len(), total, selected.len()));
+    }
+    selected
+}
+
+pub fn is_solana_address(input: &str) -> bool {
+    (32..=44).contains(&input.len()) && input.chars().all(|c| c.is_ascii_alphanumeric() && !"0OIl".contains(c))
+}
+
+fn solana_enabled(spec: Option<&str>) -> bool {
+    parse_target_chains(spec).iter().any(|chain| chain == SOLANA_CHAIN)
+}
+
+pub fn is_hex_address(input: &str) -> bool {
+    let hex = input.strip_prefix("0x").unwrap_or(input);
+    hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// Inputs that are clearly meant as an EVM address (0x-prefixed and about 40 characters long, or 39-41 hex digits)
+// but cannot be one; returns what is wrong with them so the line can be fixed instead of silently skipped
+pub fn address_problem(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    let (body, prefixed) = match trimmed.strip_prefix("0x") {
+        Some(body) => (body, true),
+        None => (trimmed, false),
+    };
+    let length = body.chars().count();
+    if !(39..=41).contains(&length) || (!prefixed && !body.chars().all(|c| c.is_ascii_hexdigit())) {
+        return None;
+    }
+    if let Some((i, c)) = body.chars().enumerate().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        let position = i + if prefixed { 3 } else { 1 };
+        return Some(tr!("address.non_hex", position, c.escape_debug()));
+    }
+    (length != 40).then(|| tr!("address.bad_length", length))
+}
+
+// a mixed-case address whose case does not match its EIP-55 checksum most likely has a mistyped character
+fn checksum_mismatch(input: &str, checksummed: &str) -> bool {
+    let body = input.strip_prefix("0x").unwrap_or(input);
+    let mixed = body.chars().any(|c| c.is_ascii_lowercase()) && body.chars().any(|c| c.is_ascii_uppercase());
+    mixed && body != &checksummed[2..]
+}
+
+pub fn normalize_address(input: &str) -> Option<String> {
+    if !is_hex_address(input) {
+        return None;
+    }
+    let address = ethers::types::Address::from_str(input.strip_prefix("0x").unwrap_or(input)).ok()?;
+    Some(ethers::utils::to_checksum(&address, None))
+}
+
+fn validate_wallet_file(input: Option<&Path>, chains_spec: Option<&str>) -> Result<()> {
+    let (source, rows) = read_wallet_rows(input)?;
+    let mut address_count = 0;
+    let mut private_key_count = 0;
+    let mut unrecognized = Vec::new();
+    let solana = solana_enabled(chains_spec);
+
+    for row in &rows {
+        let (normalized, is_private_key) = identify_input(&row.value);
+
+        if is_private_key && private_key_to_address(normalized).is_some() {
+            private_key_count += 1;
+        } else if !is_private_key && (is_hex_address(normalized) || (solana && is_solana_address(normalized))) {
+            address_count += 1;
+        } else {
+            unrecognized.push(row);
+        }
+    }
+
+    println!("{}", console_text(&tr!("validate.file", source, rows.len())));
+    println!("{}", console_text(&tr!("validate.addresses", address_count)));
+    println!("{}", console_text(&tr!("validate.private_keys", private_key_count)));
+    println!("{}", console_text(&tr!("validate.unrecognized", if unrecognized.is_empty() { "✓" } else { "✗" }, unrecognized.len())));
+    for row in &unrecognized {
+        match address_problem(&row.value) {
+            Some(problem) => println!("{}", tr!("validate.malformed_line", row.line, row.value.trim(), problem)),
+            None => println!("{}", tr!("validate.unrecognized_line", row.line, mask_private_key(row.value.trim()))),
+        }
+    }
+
+    if !unrecognized.is_empty() {
+        return Err(anyhow::anyhow!(tr!("validate.failed", source, unrecognized.len())));
+    }
+    Ok(())
+}
+
+fn resolve_addresses(input: Option<&Path>, output: Option<&Path>, chains_spec: Option<&str>) -> Result<()> {
+    let addresses = load_wallet_addresses(input, &parse_target_chains(chains_spec))?;
+    let content: String = addresses.iter().map(|wallet| format!("{}\n", wallet.address)).collect();
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            info!("{}", tr!("resolve.written", addresses.len(), path.display()));
+        }
+        None => io::stdout().write_all(content.as_bytes())?,
+    }
+    Ok(())
+}
+
+pub fn parse_timestamp(hex_timestamp: &str) -> Option<i64> {
+    let timestamp_str = hex_timestamp.strip_prefix("0x").unwrap_or(hex_timestamp);
+    u64::from_str_radix(timestamp_str, 16).ok().map(|ts| ts as i64)
+}
+
+pub fn format_timestamp(hex_timestamp: &str) -> String {
+    match parse_timestamp(hex_timestamp) {
+        Some(ts) => format_epoch(ts),
+        None => t("time.parse_failed").to_string(),
+    }
+}
+
+// hex wei -> decimal ether without trailing zeros
+pub fn format_ether_value(hex: &str) -> Option<String> {
+    let digits = hex.trim().trim_start_matches("0x");
+    let wei = if digits.is_empty() { ethers::types::U256::zero() } else { ethers::types::U256::from_str_radix(digits, 16).ok()? };
+    let ether = ethers::utils::format_ether(wei);
+    Some(if ether.contains('.') { ether.trim_end_matches('0').trim_end_matches('.').to_string() } else { ether })
+}
+
+pub fn format_epoch(ts: i64) -> String {
+    let settings = TIME_SETTINGS.get_or_init(TimeSettings::default);
+    format_epoch_as(ts, &settings.format).unwrap_or_else(|| t("time.out_of_range").to_string())
+}
+
+fn format_epoch_as(ts: i64, format: &str) -> Option<String> {
+    let settings = TIME_SETTINGS.get_or_init(TimeSettings::default);
+    let dt = DateTime::<Utc>::from_timestamp(ts, 0)?;
+    Some(match settings.zone {
+        TimeZoneMode::Local => DateTime::<Local>::from(dt).format(format).to_string(),
+        TimeZoneMode::Utc => dt.format(format).to_string(),
+        TimeZoneMode::Named(tz) => dt.with_timezone(&tz).format(format).to_string(),
+    })
+}
+
+fn parse_formatted_time(text: &str) -> Option<i64> {
+    let settings = TIME_SETTINGS.get_or_init(TimeSettings::default);
+    let naive = NaiveDateTime::parse_from_str(text, &settings.format).ok()?;
+    match settings.zone {
+        TimeZoneMode::Local => Local.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp()),
+        TimeZoneMode::Utc => Some(naive.and_utc().timestamp()),
+        TimeZoneMode::Named(tz) => tz.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp()),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum QueryStatus {
+    Found,
+    NoTransactions,
+    NoData,
+    ParseError,
+    NetworkError,
+    Timeout,
+}
+
+impl QueryStatus {
+    const ERRORS: [QueryStatus; 4] = [QueryStatus::NoData, QueryStatus::ParseError, QueryStatus::NetworkError, QueryStatus::Timeout];
+
+    fn key(self) -> &'static str {
+        match self {
+            QueryStatus::Found => "status.found",
+            QueryStatus::NoTransactions => "status.no_transactions",
+            QueryStatus::NoData => "status.no_data",
+            QueryStatus::ParseError => "status.parse_error",
+            QueryStatus::NetworkError => "status.network_error",
+            QueryStatus::Timeout => "status.timeout",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        t(self.key())
+    }
+
+    fn is_error(self) -> bool {
+        Self::ERRORS.contains(&self)
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [QueryStatus::Found, QueryStatus::NoTransactions]
+            .into_iter()
+            .chain(Self::ERRORS)
+            .find(|status| i18n::t_all(status.key()).contains(&label))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AccountType {
+    Eoa,
+    Contract,
+}
+
+impl AccountType {
+    fn label(self) -> &'static str {
+        match self {
+            AccountType::Eoa => "EOA",
+            AccountType::Contract => t("account.contract"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    In,
+    Out,
+    SelfTransfer,
+    ContractCreation,
+    Other,
+}
+
+impl Direction {
+    fn label(self) -> &'static str {
+        match self {
+            Direction::In => t("direction.in"),
+            Direction::Out => t("direction.out"),
+            Direction::SelfTransfer => t("direction.self"),
+            Direction::ContractCreation => t("direction.contract_creation"),
+            Direction::Other => t("direction.other"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub address: String,
+    pub tx_hash: String,
+    pub tx_time: String,
+    pub tx_chain: String,
+    pub tx_epoch: Option<i64>,
+    pub status: QueryStatus,
+    pub label: Option<String>,
+    pub account_type: Option<AccountType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_to: Option<String>,
+    // native amount in ether units, e.g. "0.25"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_value: Option<String>,
+    // earliest transaction on the chain, only filled with QUERY_SPAN=1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_tx_epoch: Option<i64>,
+    // QUERY_MODE=tokens: the transferred token; tx_value is then the token amount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_contract: Option<String>,
+    // QUERY_MODE=custom: the value found at RPC_RESULT_PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_value: Option<String>,
+    // why the query ended in an error status: the RPC error message or the last request failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // which provider supplied the row, only recorded when SECONDARY_PROVIDER is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+impl QueryResult {
+    fn found(address: &str, chain: &str, tx: &Transaction) -> Self {
+        let tx_epoch = parse_timestamp(&tx.timestamp);
+        QueryResult {
+            tx_hash: tx.hash.clone(),
+            tx_time: tx_epoch.map(format_epoch).unwrap_or_else(|| format_timestamp(&tx.timestamp)),
+            tx_epoch: tx_epoch.filter(|ts| DateTime::<Utc>::from_timestamp(*ts, 0).is_some()),
+            tx_from: tx.from.clone(),
+            tx_to: tx.to.clone(),
+            tx_value: tx.value.as_deref().and_then(format_ether_value),
+            ..QueryResult::empty(address, chain, QueryStatus::Found)
+        }
+    }
+
+    // a row without a transaction; also the base other rows override field by field
+    pub fn empty(address: &str, chain: &str, status: QueryStatus) -> Self {
+        QueryResult {
+            address: address.to_string(),
+            tx_hash: status.label().to_string(),
+            tx_time: NO_TX_TIME.to_string(),
+            tx_chain: chain.to_string(),
+            tx_epoch: None,
+            status,
+            label: None,
+            account_type: None,
+            latency_ms: None,
+            tx_from: None,
+            tx_to: None,
+            tx_value: None,
+            first_tx_epoch: None,
+            token_symbol: None,
+            token_contract: None,
+            rpc_value: None,
+            error: None,
+            provider: None,
+        }
+    }
+
+    // relative to the queried wallet; None when the transaction carries no sender
+    pub fn direction(&self) -> Option<Direction> {
+        let from = self.tx_from.as_deref()?;
+        let to = self.tx_to.as_deref().filter(|to| !to.is_empty() && *to != "0x");
+        let sent = from.eq_ignore_ascii_case(&self.address);
+        let received = to.is_some_and(|to| to.eq_ignore_ascii_case(&self.address));
+        Some(match (sent, received, to) {
+            (true, true, _) => Direction::SelfTransfer,
+            (true, false, None) => Direction::ContractCreation,
+            (true, false, Some(_)) => Direction::Out,
+            (false, true, _) => Direction::In,
+            (false, false, _) => Direction::Other,
+        })
+    }
+
+    fn token_transfer(address: &str, chain: &str, transfer: &TokenTransfer) -> Self {
+        let tx = Transaction {
+            hash: transfer.transaction_hash.clone(),
+            timestamp: transfer.timestamp.map(|ts| format!("0x{:x}", ts)).unwrap_or_default(),
+            blockchain: chain.to_string(),
+            from: transfer.from_address.clone(),
+            to: transfer.to_address.clone(),
+            value: None,
+        };
+        QueryResult {
+            tx_value: transfer.value.clone(),
+            token_symbol: transfer.token_symbol.clone(),
+            token_contract: transfer.contract_address.clone(),
+            ..QueryResult::found(address, chain, &tx)
+        }
+    }
+
+    fn with_latency(self, latency_ms: u64) -> Self {
+        QueryResult { latency_ms: Some(latency_ms), ..self }
+    }
+
+    fn with_error(self, error: impl Into<String>) -> Self {
+        QueryResult { error: Some(error.into()), ..self }
+    }
+
+    // whole days between the first and the last transaction
+    pub fn span_days(&self) -> Option<i64> {
+        Some((self.tx_epoch? - self.first_tx_epoch?).max(0) / 86_400)
+    }
+
+    // the status column wins when the output has one; otherwise the Hash cell carries the status label, and the
+    // configured EMPTY_HASH_TEXT could stand for any status, so it is restored as no data to be queried again
+    fn restore(address: &str, chain: &str, tx_time: &str, tx_hash: &str, status: Option<&str>, label: Option<String>) -> Self {
+        let status = status
+            .and_then(QueryStatus::from_label)
+            .or_else(|| QueryStatus::from_label(tx_hash))
+            .or_else(|| (placeholders().hash.as_deref() == Some(tx_hash)).then_some(QueryStatus::NoData));
+        match status.filter(|status| *status != QueryStatus::Found) {
+            Some(status) => QueryResult { label, ..QueryResult::empty(address, chain, status) },
+            None => QueryResult {
+                tx_hash: tx_hash.to_string(),
+                tx_time: tx_time.to_string(),
+                tx_epoch: parse_formatted_time(tx_time),
+                label,
+                ..QueryResult::empty(address, chain, QueryStatus::Found)
+            },
+        }
+    }
+}
+
+// the more recent transaction of the two providers wins; an answer also fills in for a failed primary query
+pub fn merge_provider_results(primary: QueryResult, secondary: Option<QueryResult>) -> QueryResult {
+    let Some(secondary) = secondary else {
+        return primary;
+    };
+    let newer = match (primary.status, secondary.status) {
+        (QueryStatus::Found, QueryStatus::Found) => secondary.tx_epoch > primary.tx_epoch,
+        (_, QueryStatus::Found) => true,
+        (status, QueryStatus::NoTransactions) => status.is_error(),
+        _ => false,
+    };
+    if !newer {
+        return primary;
+    }
+    QueryResult {
+        label: primary.label,
+        account_type: primary.account_type,
+        latency_ms: primary.latency_ms,
+        first_tx_epoch: primary.first_tx_epoch,
+        ..secondary
+    }
+}
+
+fn is_dormant(row: &QueryResult, inactive_days: i64) -> Option<bool> {
+    match row.status {
+        QueryStatus::Found => row.tx_epoch.map(|epoch| Utc::now().timestamp() - epoch > inactive_days * 86_400),
+        QueryStatus::NoTransactions => Some(true),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointHeader {
+    input_hash: String,
+}
+
+struct Checkpoint {
+    writer: std::sync::Mutex<io::BufWriter<File>>,
+    completed: HashSet<(String, String)>,
+}
+
+impl Checkpoint {
+    fn open(input_hash: &str, fresh: bool) -> Result<(Self, Vec<QueryResult>)> {
+        let resumed = if fresh { Vec::new() } else { Self::load(input_hash) };
+
+        let file = if resumed.is_empty() {
+            let mut file = File::create(CHECKPOINT_FILE)?;
+            serde_json::to_writer(&mut file, &CheckpointHeader { input_hash: input_hash.to_string() })?;
+            file.write_all(b"\n")?;
+            file
+        } else {
+            std::fs::OpenOptions::new().append(true).open(CHECKPOINT_FILE)?
+        };
+
+        let completed = resumed.iter().map(|r| (r.address.clone(), r.tx_chain.clone())).collect();
+        let checkpoint = Checkpoint {
+            writer: std::sync::Mutex::new(io::BufWriter::new(file)),
+            completed,
+        };
+        Ok((checkpoint, resumed))
+    }
+
+    fn load(input_hash: &str) -> Vec<QueryResult> {
+        let Ok(file) = File::open(CHECKPOINT_FILE) else {
+            return Vec::new();
+        };
+        let mut lines = io::BufReader::new(file).lines().map_while(|line| line.ok());
+
+        let header = lines.next().and_then(|line| serde_json::from_str::<CheckpointHeader>(&line).ok());
+        if header.is_none_or(|h| h.input_hash != input_hash) {
+            warn!("{}", tr!("checkpoint.mismatch", CHECKPOINT_FILE));
+            return Vec::new();
+        }
+
+        lines
+            .filter_map(|line| serde_json::from_str::<QueryResult>(&line).ok())
+            .filter(|r| !r.status.is_error())
+            .collect()
+    }
+
+    fn is_completed(&self, address: &str, chain: &str) -> bool {
+        self.completed.contains(&(address.to_string(), chain.to_string()))
+    }
+
+    fn mark_completed(&mut self, results: &[QueryResult]) {
+        self.completed.extend(results.iter().map(|r| (r.address.clone(), r.tx_chain.clone())));
+    }
+
+    fn record(&self, results: &[QueryResult]) {
+        let mut writer = self.writer.lock().unwrap();
+        let written = results.iter().try_for_each(|r| {
+            serde_json::to_writer(&mut *writer, r)?;
+            writer.write_all(b"\n")?;
+            Ok::<_, anyhow::Error>(())
+        });
+        if let Err(e) = written.and_then(|_| writer.flush().map_err(Into::into)) {
+            warn!("{}", tr!("checkpoint.write_failed", e));
+        }
+    }
+}
+
+fn input_hash(addresses: &[String], chains: &[String]) -> String {
+    let mut hasher = Keccak256::new();
+    for address in addresses {
+        hasher.update(address.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher.update(chains.join(",").as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResultsCache {
+    key: String,
+    created: String,
+    results: Vec<QueryResult>,
+}
+
+fn load_use_cache() -> bool {
+    std::env::var("USE_CACHE").is_ok_and(|v| v.trim() == "1")
+}
+
+// everything that decides what the API returns: the address set (in any order), chains, query mode and provider
+fn results_cache_key(addresses: &[String], chains: &[String], config: &QueryConfig) -> String {
+    let mut sorted = addresses.to_vec();
+    sorted.sort();
+    let mut hasher = Keccak256::new();
+    hasher.update(input_hash(&sorted, chains).as_bytes());
+    hasher.update(format!("\n{}\n{}\n{}", config.query_mode, config.provider.name(), config.query_span).as_bytes());
+    if let Some(secondary) = &config.secondary {
+        hasher.update(format!("\n{}", secondary.name()).as_bytes());
+    }
+    if let Some(custom) = &config.custom_method {
+        hasher.update(format!("\n{}\n{}\n{}", custom.method, custom.params, custom.result_path).as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn load_results_cache(key: &str) -> Option<ResultsCache> {
+    let text = std::fs::read_to_string(RESULTS_CACHE_FILE).ok()?;
+    match serde_json::from_str::<ResultsCache>(&text) {
+        Ok(cache) if cache.key == key => Some(cache),
+        Ok(_) => {
+            info!("{}", tr!("cache.mismatch", RESULTS_CACHE_FILE));
+            None
+        }
+        Err(e) => {
+            warn!("{}", tr!("cache.read_failed", RESULTS_CACHE_FILE, e));
+            None
+        }
+    }
+}
+
+fn write_results_cache(key: &str, results: &[QueryResult]) {
+    let cache = ResultsCache { key: key.to_string(), created: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(), results: results.to_vec() };
+    let written = File::create(RESULTS_CACHE_FILE).map_err(anyhow::Error::from).and_then(|file| Ok(serde_json::to_writer(io::BufWriter::new(file), &cache)?));
+    match written {
+        Ok(()) => info!("{}", tr!("cache.saved", RESULTS_CACHE_FILE, results.len())),
+        Err(e) => warn!("{}", tr!("cache.write_failed", RESULTS_CACHE_FILE, e)),
+    }
+}
+
+fn merge_results(resumed: Vec<QueryResult>, fresh: Vec<QueryResult>, addresses: &[String]) -> Vec<QueryResult> {
+    let fresh_keys: HashSet<(String, String)> = fresh.iter().map(|r| (r.address.clone(), r.tx_chain.clone())).collect();
+    let mut merged: Vec<QueryResult> = resumed
+        .into_iter()
+        .filter(|r| !fresh_keys.contains(&(r.address.clone(), r.tx_chain.clone())))
+        .chain(fresh)
+        .collect();
+
+    let positions: std::collections::HashMap<&str, usize> =
+        addresses.iter().enumerate().rev().map(|(i, addr)| (addr.as_str(), i)).collect();
+    merged.sort_by_key(|r| positions.get(r.address.as_str()).copied().unwrap_or(usize::MAX));
+    merged
+}
+
+#[derive(Deserialize)]
+struct PreviousCsvRow {
+    #[serde(rename = "链", alias = "Chain")]
+    chain: String,
+    #[serde(rename = "地址", alias = "Address")]
+    address: String,
+    #[serde(rename = "备注", alias = "Label", default)]
+    label: Option<String>,
+    #[serde(rename = "时间", alias = "Time")]
+    tx_time: String,
+    #[serde(rename = "Hash")]
+    tx_hash: String,
+    #[serde(rename = "状态", alias = "Status", default)]
+    status: Option<String>,
+}
+
+pub fn load_previous_results(path: &Path) -> Result<Vec<QueryResult>> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "xlsx" | "xls" | "ods" => load_previous_xlsx(path),
+        "json" => Ok(serde_json::from_reader(io::BufReader::new(File::open(path)?))?),
+        "jsonl" => io::BufReader::new(File::open(path)?)
+            .lines()
+            .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect(),
+        "csv" => {
+            let text = std::fs::read_to_string(path)?;
+            let mut reader = ReaderBuilder::new().delimiter(load_csv_delimiter(&text)).from_reader(text.as_bytes());
+            reader
+                .deserialize::<PreviousCsvRow>()
+                .map(|row| {
+                    let row = row?;
+                    let label = row.label.filter(|l| !l.is_empty());
+                    Ok(QueryResult::restore(&row.address, &normalize_chain(&row.chain), &row.tx_time, &row.tx_hash, row.status.as_deref(), label))
+                })
+                .collect()
+        }
+        _ => anyhow::bail!(tr!("retry.unsupported_file", path.display())),
+    }
+}
+
+fn load_previous_xlsx(path: &Path) -> Result<Vec<QueryResult>> {
+    let mut workbook = calamine::open_workbook_auto(path)?;
+    let mut results = Vec::new();
+
+    for sheet in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&sheet)?;
+        let mut rows = range.rows().map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>());
+        let Some(header) = rows.next() else {
+            continue;
+        };
+        let is = |text: &str, key: &'static str| i18n::t_all(key).contains(&text);
+        let col = |key: &'static str| header.iter().position(|h| is(h, key));
+        let cell = |row: &[String], i: Option<usize>| i.and_then(|i| row.get(i)).cloned().unwrap_or_default();
+        let label_col = col("header.label");
+        let label = |row: &[String]| Some(cell(row, label_col)).filter(|l| !l.is_empty());
+        let first = header.first().map(String::as_str).unwrap_or_default();
+
+        if is(first, "header.chain") {
+            let (chain, address, time) = (col("header.chain"), col("header.address"), col("header.time"));
+            let hash = header.iter().position(|h| h == "Hash");
+            for row in rows.filter(|row| !cell(row, address).is_empty()) {
+                let (addr, chain) = (cell(&row, address), normalize_chain(&cell(&row, chain)));
+                results.push(QueryResult::restore(&addr, &chain, &cell(&row, time), &cell(&row, hash), None, label(&row)));
+            }
+        } else if is(first, "header.wallet_address") && col("header.tx_hash").is_some() {
+            let time_prefixes = i18n::t_all("header.last_tx_time").map(|template| template.split("{}").next().unwrap_or_default());
+            let time = header.iter().position(|h| time_prefixes.iter().any(|prefix| h.starts_with(prefix)));
+            let hash = col("header.tx_hash");
+            for row in rows.filter(|row| !cell(row, Some(0)).is_empty()) {
+                let addr = cell(&row, Some(0));
+                results.push(QueryResult::restore(&addr, &normalize_chain(&sheet), &cell(&row, time), &cell(&row, hash), None, label(&row)));
+            }
+        } else if is(first, "header.wallet_address") {
+            let time_suffixes = i18n::t_all("header.chain_last_tx_time").map(|template| template.trim_start_matches("{}"));
+            let chain_cols: Vec<(usize, String)> = header
+                .iter()
+                .enumerate()
+                .filter_map(|(i, h)| time_suffixes.iter().find_map(|suffix| h.strip_suffix(suffix)).map(|chain| (i, normalize_chain(chain))))
+                .collect();
+            for row in rows.filter(|row| !cell(row, Some(0)).is_empty()) {
+                let addr = cell(&row, Some(0));
+                for &(time, ref chain) in &chain_cols {
+                    let tx_hash = cell(&row, Some(time + 1));
+                    if !tx_hash.is_empty() {
+                        results.push(QueryResult::restore(&addr, chain, &cell(&row, Some(time)), &tx_hash, None, label(&row)));
+                    }
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Change {
+    Activated,
+    NewTransaction,
+    Deactivated,
+    NewAddress,
+}
+
+impl Change {
+    pub fn label(self) -> &'static str {
+        match self {
+            Change::Activated => t("change.activated"),
+            Change::NewTransaction => t("change.new_transaction"),
+            Change::Deactivated => t("change.deactivated"),
+            Change::NewAddress => t("change.new_address"),
+        }
+    }
+}
+
+pub struct ResultChange<'a> {
+    pub change: Change,
+    pub previous: Option<&'a QueryResult>,
+    pub current: &'a QueryResult,
+}
+
+// Rows are matched by (address, chain). Chains missing from either run and rows that failed in either run
+// say nothing about the wallet, so they are left out rather than reported as changes.
+pub fn diff_results<'a>(previous: &'a [QueryResult], current: &'a [QueryResult]) -> Vec<ResultChange<'a>> {
+    let key = |row: &QueryResult| (row.address.to_lowercase(), row.tx_chain.clone());
+    let before: std::collections::HashMap<(String, String), &QueryResult> = previous.iter().map(|row| (key(row), row)).collect();
+    let chains: HashSet<&str> = previous.iter().map(|row| row.tx_chain.as_str()).collect();
+
+    let mut changes = Vec::new();
+    for row in current.iter().filter(|row| !row.status.is_error() && chains.contains(row.tx_chain.as_str())) {
+        let previous = before.get(&key(row)).copied();
+        let change = match previous.map(|prev| prev.status) {
+            None => Some(Change::NewAddress),
+            Some(status) if status.is_error() => None,
+            Some(QueryStatus::NoTransactions) => (row.status == QueryStatus::Found).then_some(Change::Activated),
+            Some(_) if row.status == QueryStatus::NoTransactions => Some(Change::Deactivated),
+            Some(_) => previous.filter(|prev| !prev.tx_hash.eq_ignore_ascii_case(&row.tx_hash)).map(|_| Change::NewTransaction),
+        };
+        if let Some(change) = change {
+            changes.push(ResultChange { change, previous, current: row });
+        }
+    }
+    changes
+}
+
+fn change_header() -> Vec<String> {
+    let zone_label = TIME_SETTINGS.get_or_init(TimeSettings::default).zone.label();
+    vec![
+        t("header.chain").to_string(),
+        t("header.wallet_address").to_string(),
+        t("header.change").to_string(),
+        tr!("header.previous_tx_time", zone_label),
+        t("header.previous_tx_hash").to_string(),
+        tr!("header.last_tx_time", zone_label),
+        t("header.tx_hash").to_string(),
+    ]
+}
+
+fn write_changes_sheet(workbook: &mut Workbook, changes: &[ResultChange], formats: &SheetFormats) -> Result<()> {
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.changes"))?;
+    for (col, (header, width)) in change_header().iter().zip([15, 45, 12, 25, 70, 25, 70]).enumerate() {
+        worksheet.write_string_with_format(0, col as u16, header, &formats.header)?;
+        worksheet.set_column_width(col as u16, width)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofilter(0, 0, changes.len() as u32, 6)?;
+
+    for (i, change) in changes.iter().enumerate() {
+        let row_idx = (i + 1) as u32;
+        let format = formats.for_status(change.current.status);
+        worksheet.write_string_with_format(row_idx, 0, chain_display_name(&change.current.tx_chain), format)?;
+        write_address_cell(worksheet, row_idx, 1, change.current, format)?;
+        worksheet.write_string_with_format(row_idx, 2, change.change.label(), format)?;
+        if let Some(previous) = change.previous {
+            worksheet.write_string_with_format(row_idx, 3, placeholders().time(previous), format)?;
+            write_hash_cell(worksheet, row_idx, 4, previous, format)?;
+        }
+        worksheet.write_string_with_format(row_idx, 5, placeholders().time(change.current), format)?;
+        write_hash_cell(worksheet, row_idx, 6, change.current, format)?;
+    }
+    Ok(())
+}
+
+// counts per error type on top, then one row per (address, chain) that ended in an error
+fn write_errors_sheet(workbook: &mut Workbook, results: &[QueryResult], sheet_chains: &[String], formats: &SheetFormats) -> Result<()> {
+    let mut failed: Vec<&QueryResult> = results.iter().filter(|r| r.status.is_error()).collect();
+    let status_order = |r: &QueryResult| QueryStatus::ERRORS.iter().position(|s| *s == r.status);
+    let chain_order = |r: &QueryResult| sheet_chains.iter().position(|c| *c == r.tx_chain);
+    failed.sort_by_key(|r| (status_order(r), chain_order(r)));
+
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.errors"))?;
+    worksheet.write_string_with_format(0, 0, t("header.error_type"), &formats.header)?;
+    worksheet.write_string_with_format(0, 1, t("header.count"), &formats.header)?;
+    let mut row_idx = 1;
+    for status in QueryStatus::ERRORS {
+        let count = failed.iter().filter(|r| r.status == status).count();
+        if count > 0 {
+            worksheet.write_string(row_idx, 0, status.label())?;
+            worksheet.write_number(row_idx, 1, count as f64)?;
+            row_idx += 1;
+        }
+    }
+
+    let header_row = row_idx + 1;
+    let headers = [t("header.address"), t("header.chain"), t("header.error_type"), t("header.error_message")];
+    for (col, (header, width)) in headers.iter().zip([45, 15, 12, 80]).enumerate() {
+        worksheet.write_string_with_format(header_row, col as u16, *header, &formats.header)?;
+        worksheet.set_column_width(col as u16, width)?;
+    }
+    worksheet.set_freeze_panes(header_row + 1, 0)?;
+    worksheet.autofilter(header_row, 0, header_row + failed.len() as u32, 3)?;
+    for (i, row) in failed.iter().enumerate() {
+        let row_idx = header_row + 1 + i as u32;
+        worksheet.write_string(row_idx, 0, &row.address)?;
+        worksheet.write_string(row_idx, 1, chain_display_name(&row.tx_chain))?;
+        worksheet.write_string(row_idx, 2, row.status.label())?;
+        worksheet.write_string(row_idx, 3, row.error.as_deref().unwrap_or_default())?;
+    }
+    Ok(())
+}
+
+fn write_changes_csv(path: &Path, changes: &[ResultChange]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(change_header())?;
+    for change in changes {
+        let placeholders = placeholders();
+        let (previous_time, previous_hash) = change.previous.map(|prev| (placeholders.time(prev), placeholders.hash(prev))).unwrap_or_default();
+        writer.write_record([
+            change.current.tx_chain.as_str(),
+            change.current.address.as_str(),
+            change.change.label(),
+            previous_time,
+            previous_hash,
+            placeholders.time(change.current),
+            placeholders.hash(change.current),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn previous_inputs(previous: &[QueryResult]) -> (Vec<Wallet>, Vec<String>) {
+    let mut wallets: Vec<Wallet> = Vec::new();
+    let mut chains = Vec::new();
+    for row in previous {
+        match wallets.iter_mut().find(|w| w.address == row.address) {
+            Some(wallet) => wallet.label = wallet.label.take().or_else(|| row.label.clone()),
+            None => wallets.push(Wallet { address: row.address.clone(), label: row.label.clone() }),
+        }
+        if !chains.contains(&row.tx_chain) {
+            chains.push(row.tx_chain.clone());
+        }
+    }
+    (wallets, chains)
+}
+
+struct AdaptiveConcurrency {
+    semaphore: Semaphore,
+    enabled: bool,
+    min: usize,
+    max: usize,
+    limit: AtomicUsize,
+    successes: AtomicUsize,
+    debt: AtomicUsize,
+    last_decrease: std::sync::Mutex<Option<Instant>>,
+}
+
+struct ConcurrencyPermit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    owner: &'a AdaptiveConcurrency,
+}
+
+impl Drop for ConcurrencyPermit<'_> {
+    fn drop(&mut self) {
+        let paid = self
+            .owner
+            .debt
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |debt| debt.checked_sub(1))
+            .is_ok();
+        if let (true, Some(permit)) = (paid, self.permit.take()) {
+            permit.forget();
+        }
+    }
+}
+
+impl AdaptiveConcurrency {
+    fn new(initial: usize, bounds: Option<(usize, usize)>) -> Self {
+        let (min, max) = bounds.unwrap_or((initial, initial));
+        AdaptiveConcurrency {
+            semaphore: Semaphore::new(initial),
+            enabled: bounds.is_some(),
+            min,
+            max,
+            limit: AtomicUsize::new(initial),
+            successes: AtomicUsize::new(0),
+            debt: AtomicUsize::new(0),
+            last_decrease: std::sync::Mutex::new(None),
+        }
+    }
+
+    async fn acquire(&self) -> ConcurrencyPermit<'_> {
+        ConcurrencyPermit {
+            permit: Some(self.semaphore.acquire().await.unwrap()),
+            owner: self,
+        }
+    }
+
+    fn on_success(&self) {
+        if !self.enabled {
+            return;
+        }
+        let limit = self.limit.load(Ordering::SeqCst);
+        if self.successes.fetch_add(1, Ordering::SeqCst) + 1 < limit || limit >= self.max {
+            return;
+        }
+        self.successes.store(0, Ordering::SeqCst);
+        if self.limit.compare_exchange(limit, limit + 1, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            let repaid = self.debt.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |debt| debt.checked_sub(1)).is_ok();
+            if !repaid {
+                self.semaphore.add_permits(1);
+            }
+        }
+    }
+
+    fn on_throttled(&self) {
+        if !self.enabled {
+            return;
+        }
+        let mut last_decrease = self.last_decrease.lock().unwrap();
+        if last_decrease.is_some_and(|at| at.elapsed() < Duration::from_secs(1)) {
+            return;
+        }
+        *last_decrease = Some(Instant::now());
+
+        let limit = self.limit.load(Ordering::SeqCst);
+        let reduced = (limit / 2).max(self.min);
+        if reduced < limit {
+            self.limit.store(reduced, Ordering::SeqCst);
+            self.successes.store(0, Ordering::SeqCst);
+            self.debt.fetch_add(limit - reduced, Ordering::SeqCst);
+            warn!("{}", tr!("concurrency.throttled", reduced));
+        }
+    }
+}
+
+// quota reported by the provider in response headers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct RateLimitHeaders {
+    pub(crate) remaining: Option<u64>,
+    pub(crate) limit: Option<u64>,
+    // unix seconds
+    pub(crate) reset_epoch: Option<i64>,
+}
+
+impl RateLimitHeaders {
+    // keeps the lowest remaining count and the most recently reported limit and reset
+    fn merge(self, newer: RateLimitHeaders) -> RateLimitHeaders {
+        let remaining = match (self.remaining, newer.remaining) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        RateLimitHeaders { remaining, limit: newer.limit.or(self.limit), reset_epoch: newer.reset_epoch.or(self.reset_epoch) }
+    }
+}
+
+// reset may be seconds from now (RateLimit draft), a unix timestamp in seconds or in milliseconds; `60s` style values are accepted too
+pub(crate) fn parse_rate_limit_headers(headers: &reqwest::header::HeaderMap, now: i64) -> Option<RateLimitHeaders> {
+    let first = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            let value = headers.get(*name)?.to_str().ok()?;
+            // "100, 100;w=60" style lists: the first number is the one for the current window
+            let number = value.split([',', ';']).next()?.trim().trim_end_matches('s');
+            number.parse::<f64>().ok().filter(|n| n.is_finite() && *n >= 0.0)
+        })
+    };
+    let remaining = first(&QUOTA_REMAINING_HEADERS).map(|n| n as u64);
+    let limit = first(&QUOTA_LIMIT_HEADERS).map(|n| n as u64);
+    let reset_epoch = first(&QUOTA_RESET_HEADERS).map(|n| match n as i64 {
+        n if n >= 1_000_000_000_000 => n / 1000,
+        n if n >= 1_000_000_000 => n,
+        n => now + n,
+    });
+    (remaining.is_some() || reset_epoch.is_some()).then_some(RateLimitHeaders { remaining, limit, reset_epoch })
+}
+
+fn load_quota_low_remaining() -> u64 {
+    std::env::var("QUOTA_LOW_REMAINING").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_QUOTA_LOW_REMAINING)
+}
+
+struct PooledClient {
+    client: Client,
+    label: String,
+    requests: AtomicU32,
+    errors: AtomicU32,
+    consecutive_errors: AtomicU32,
+    skip_until: std::sync::Mutex<Option<Instant>>,
+}
+
+struct ClientPool {
+    clients: Vec<PooledClient>,
+    next: AtomicUsize,
+}
+
+impl ClientPool {
+    fn new(proxy_urls: &[String], settings: &ClientSettings) -> Result<Self> {
+        let entry = |client: Client, label: String| PooledClient {
+            client,
+            label,
+            requests: AtomicU32::new(0),
+            errors: AtomicU32::new(0),
+            consecutive_errors: AtomicU32::new(0),
+            skip_until: std::sync::Mutex::new(None),
+        };
+        let clients = if proxy_urls.is_empty() {
+            vec![entry(build_client(None, settings)?, t("proxy.direct").to_string())]
+        } else {
+            proxy_urls
+                .iter()
+                .map(|url| Ok(entry(build_client(Some(url), settings)?, mask_proxy_url(url))))
+                .collect::<Result<Vec<_>>>()?
+        };
+        Ok(ClientPool { clients, next: AtomicUsize::new(0) })
+    }
+
+    fn pick(&self) -> usize {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        (0..self.clients.len())
+            .map(|offset| (start + offset) % self.clients.len())
+            .find(|&idx| !matches!(*self.clients[idx].skip_until.lock().unwrap(), Some(until) if now < until))
+            .unwrap_or(start % self.clients.len())
+    }
+
+    fn client(&self, idx: usize) -> &Client {
+        &self.clients[idx].client
+    }
+
+    fn report(&self, idx: usize, ok: bool) {
+        let entry = &self.clients[idx];
+        entry.requests.fetch_add(1, Ordering::Relaxed);
+        if ok {
+            entry.consecutive_errors.store(0, Ordering::Relaxed);
+            return;
+        }
+        entry.errors.fetch_add(1, Ordering::Relaxed);
+        let failures = entry.consecutive_errors.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.clients.len() > 1 && failures >= PROXY_FAILURE_THRESHOLD {
+            entry.consecutive_errors.store(0, Ordering::Relaxed);
+            *entry.skip_until.lock().unwrap() = Some(Instant::now() + Duration::from_secs(PROXY_COOLDOWN_SECS));
+            warn!("{}", tr!("proxy.cooldown", entry.label, failures, PROXY_COOLDOWN_SECS));
+        }
+    }
+
+    fn print_stats(&self) {
+        if self.clients.len() < 2 {
+            return;
+        }
+        info!("{}", t("proxy.stats"));
+        for entry in &self.clients {
+            let requests = entry.requests.load(Ordering::Relaxed);
+            let errors = entry.errors.load(Ordering::Relaxed);
+            let rate = if requests == 0 { 0.0 } else { errors as f64 * 100.0 / requests as f64 };
+            info!("{}", tr!("proxy.stats_line", entry.label, requests, errors, format!("{:.1}", rate)));
+        }
+    }
+}
+
+struct QueryContext {
+    clients: ClientPool,
+    base_url: String,
+    solana_url: String,
+    chain_rpc_base: String,
+    api_key: String,
+    fixtures: Option<Arc<MockFixtures>>,
+    concurrency: AdaptiveConcurrency,
+    chain_limits: std::collections::HashMap<String, Semaphore>,
+    rate_limiter: Option<DefaultDirectRateLimiter>,
+    retry_delay: Duration,
+    skip_confirm: bool,
+    confirm_empty_delay: Option<Duration>,
+    query_span: bool,
+    custom_method: Option<CustomMethod>,
+    provider_name: &'static str,
+    secondary: Option<SecondaryProvider>,
+    etherscan_url: String,
+    secondary_limiter: Option<DefaultDirectRateLimiter>,
+    batch_size: usize,
+    batch_delay: Duration,
+    save_failed: bool,
+    confirmed: std::sync::Mutex<std::collections::HashMap<(String, String), QueryResult>>,
+    requests: AtomicUsize,
+    throttled: AtomicUsize,
+    quota: std::sync::Mutex<Option<RateLimitHeaders>>,
+    quota_low_remaining: u64,
+    quota_warned: AtomicBool,
+    latencies: std::sync::Mutex<Vec<u64>>,
+    stream: std::sync::Mutex<Option<StreamOutput>>,
+    keep_results: AtomicBool,
+    checkpoint: Option<Checkpoint>,
+    completed: std::sync::Mutex<Vec<QueryResult>>,
+    shutting_down: AtomicBool,
+}
+
+impl QueryContext {
+    fn new(clients: ClientPool, config: &QueryConfig, checkpoint: Option<Checkpoint>) -> Self {
+        let api_key = config.api_key.as_str();
+        let with_key = |base: &str| if api_key.is_empty() { base.to_string() } else { format!("{}/{}", base, api_key) };
+
+        QueryContext {
+            clients,
+            base_url: with_key(&config.endpoints.multichain),
+            solana_url: with_key(&config.endpoints.solana),
+            chain_rpc_base: config.endpoints.chain_rpc.clone(),
+            api_key: api_key.to_string(),
+            fixtures: match &config.provider {
+                Provider::Mock(fixtures) => Some(fixtures.clone()),
+                Provider::Ankr => None,
+            },
+            concurrency: AdaptiveConcurrency::new(config.concurrency, config.adaptive_bounds),
+            chain_limits: config.chain_concurrency.iter().map(|(chain, &limit)| (chain.clone(), Semaphore::new(limit))).collect(),
+            rate_limiter: config.rate_limit_rps.map(|rps| RateLimiter::direct(Quota::per_second(rps))),
+            retry_delay: config.retry_delay,
+            skip_confirm: config.skip_confirm,
+            confirm_empty_delay: config.confirm_empty_delay,
+            query_span: config.query_span,
+            custom_method: config.custom_method.clone(),
+            provider_name: config.provider.name(),
+            secondary: config.secondary.clone(),
+            etherscan_url: config.endpoints.etherscan.clone(),
+            secondary_limiter: config.secondary.as_ref().and_then(|s| s.rate_limit_rps).map(|rps| RateLimiter::direct(Quota::per_second(rps))),
+            batch_size: config.batch_size,
+            batch_delay: config.batch_delay,
+            save_failed: config.save_failed,
+            confirmed: std::sync::Mutex::new(std::collections::HashMap::new()),
+            requests: AtomicUsize::new(0),
+            throttled: AtomicUsize::new(0),
+            quota: std::sync::Mutex::new(None),
+            quota_low_remaining: config.quota_low_remaining,
+            quota_warned: AtomicBool::new(false),
+            latencies: std::sync::Mutex::new(Vec::new()),
+            stream: std::sync::Mutex::new(None),
+            keep_results: AtomicBool::new(true),
+            checkpoint,
+            completed: std::sync::Mutex::new(Vec::new()),
+            shutting_down: AtomicBool::new(false),
+        }
+    }
+
+    // the chain's own cap is taken first, so tasks queued behind a strict chain hold no global permit
+    async fn acquire_for(&self, chain: &str) -> (Option<SemaphorePermit<'_>>, ConcurrencyPermit<'_>) {
+        let chain_permit = match self.chain_limits.get(chain) {
+            Some(semaphore) => Some(semaphore.acquire().await.unwrap()),
+            None => None,
+        };
+        (chain_permit, self.concurrency.acquire().await)
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    fn take_completed(&self) -> Vec<QueryResult> {
+        std::mem::take(&mut *self.completed.lock().unwrap())
+    }
+
+    fn is_completed(&self, address: &str, chain: &str) -> bool {
+        self.checkpoint.as_ref().is_some_and(|cp| cp.is_completed(address, chain))
+    }
+
+    fn record(&self, results: &[QueryResult]) {
+        if let Some(cp) = &self.checkpoint {
+            cp.record(results);
+        }
+        if let Some(stream) = self.stream.lock().unwrap().as_mut() {
+            stream.write(results);
+        }
+        if self.keep_results.load(Ordering::Relaxed) {
+            self.completed.lock().unwrap().extend_from_slice(results);
+        }
+    }
+
+    // with only streaming outputs the results are already on disk, so tasks hand back nothing
+    fn keep<T: Default>(&self, value: T) -> T {
+        if self.keep_results.load(Ordering::Relaxed) {
+            value
+        } else {
+            T::default()
+        }
+    }
+
+    // elapsed time of a request that got a usable response, from send until the body was parsed
+    fn observe_latency(&self, started: Instant) -> u64 {
+        let ms = started.elapsed().as_millis() as u64;
+        self.latencies.lock().unwrap().push(ms);
+        ms
+    }
+
+    fn chain_rpc_url(&self, chain: &str) -> String {
+        let path = if chain == "zksync" { "zksync_era" } else { chain };
+        if self.api_key.is_empty() {
+            format!("{}/{}", self.chain_rpc_base, path)
+        } else {
+            format!("{}/{}/{}", self.chain_rpc_base, path, self.api_key)
+        }
+    }
+
+    fn redact_url(&self, url: &str) -> String {
+        if self.api_key.is_empty() {
+            url.to_string()
+        } else {
+            url.replace(&self.api_key, "***")
+        }
+    }
+
+    // keeps the body of a response that could not be parsed so it can be attached to a bug report
+    fn save_failed_response(&self, address: &str, chain: &str, text: &str) {
+        if !self.save_failed {
+            return;
+        }
+        let name = format!("{}_{}_{}.txt", address, sheet_name(chain), Local::now().format("%Y%m%d-%H%M%S%.3f"));
+        let path = Path::new(FAILED_RESPONSES_DIR).join(name);
+        let saved = std::fs::create_dir_all(FAILED_RESPONSES_DIR).and_then(|_| std::fs::write(&path, self.redact_url(text)));
+        match saved {
+            Ok(()) => info!("{}", tr!("query.response_saved", path.display())),
+            Err(e) => warn!("{}", tr!("query.response_save_failed", path.display(), e)),
+        }
+    }
+
+    async fn post<T: Serialize + ?Sized>(&self, payload: &T) -> Result<reqwest::Result<reqwest::Response>, Elapsed> {
+        self.post_to(&self.base_url, payload).await
+    }
+
+    async fn post_to<T: Serialize + ?Sized>(&self, url: &str, payload: &T) -> Result<reqwest::Result<reqwest::Response>, Elapsed> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.until_ready().await;
+        }
+        let idx = self.clients.pick();
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        debug!(url = %self.redact_url(url), payload = %serde_json::to_string(payload).unwrap_or_default(), "{}", t("log.sending_request"));
+        let response = timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), self.clients.client(idx).post(url).json(payload).send())
+            .await
+            .map(|r| r.map_err(|e| e.without_url()));
+        let ok = matches!(&response, Ok(Ok(r)) if !r.status().is_server_error() && r.status() != StatusCode::TOO_MANY_REQUESTS);
+        self.clients.report(idx, ok);
+        if let Ok(Ok(r)) = &response {
+            if let Some(seen) = parse_rate_limit_headers(r.headers(), Utc::now().timestamp()) {
+                self.observe_quota(seen);
+            }
+            if r.status() == StatusCode::TOO_MANY_REQUESTS {
+                self.throttled.fetch_add(1, Ordering::Relaxed);
+                self.concurrency.on_throttled();
+            } else if r.status().is_success() {
+                self.concurrency.on_success();
+            }
+        }
+        response
+    }
+
+    // close to the provider's limit: back off like on a 429 before the provider starts refusing requests
+    fn observe_quota(&self, seen: RateLimitHeaders) {
+        {
+            let mut quota = self.quota.lock().unwrap();
+            *quota = Some(quota.map_or(seen, |q| q.merge(seen)));
+        }
+        let Some(remaining) = seen.remaining.filter(|&r| r < self.quota_low_remaining) else {
+            return;
+        };
+        self.concurrency.on_throttled();
+        if !self.quota_warned.swap(true, Ordering::Relaxed) {
+            let action = if self.concurrency.enabled { t("quota.slowing") } else { t("quota.no_adaptive") };
+            warn!("{}", tr!("quota.low", remaining, self.quota_low_remaining, action));
+        }
+    }
+
+    // one attempt: send, then read and parse the body
+    async fn call<P: Serialize + ?Sized, T: DeserializeOwned>(&self, url: &str, payload: &P, id: u32) -> Result<RpcResponse<T>, QueryError> {
+        let response = match self.post_to(url, payload).await {
+            Ok(Ok(r)) => r,
+            Ok(Err(e)) => return Err(QueryError::Network(e.to_string())),
+            Err(_) => return Err(QueryError::Timeout),
+        };
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(QueryError::Throttled(status, body));
+        }
+        parse_rpc_body(&body, id).map_err(|error| QueryError::Parse { error, body })
+    }
+}
+
+// why one request attempt failed; the kind decides how many attempts a query gets
+enum QueryError {
+    Network(String),
+    Timeout,
+    // 429 or 5xx: the provider is overloaded, so waiting and retrying helps
+    Throttled(StatusCode, String),
+    Parse { error: String, body: String },
+}
+
+impl QueryError {
+    fn max_attempts(&self) -> u32 {
+        match self {
+            QueryError::Parse { .. } => PARSE_MAX_ATTEMPTS,
+            _ => MAX_RETRIES,
+        }
+    }
+
+    fn status(&self) -> QueryStatus {
+        match self {
+            // a provider that keeps answering 429/5xx is a transport problem, not a malformed response
+            QueryError::Network(_) | QueryError::Throttled(..) => QueryStatus::NetworkError,
+            QueryError::Timeout => QueryStatus::Timeout,
+            QueryError::Parse { .. } => QueryStatus::ParseError,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            QueryError::Network(e) => tr!("error.network", e),
+            QueryError::Timeout => tr!("error.timeout", REQUEST_TIMEOUT_SECS),
+            QueryError::Throttled(status, body) => tr!("error.http_status", status, body.trim().chars().take(200).collect::<String>()),
+            QueryError::Parse { error, .. } => tr!("error.parse", error),
+        }
+    }
+
+    // chain is None for a multichain request
+    fn retry_message(&self, address: &str, chain: Option<&str>, attempt: u32) -> String {
+        match (self, chain.map(chain_display_name)) {
+            (QueryError::Network(e), Some(chain)) => tr!("query.network_retry", address, chain, attempt, e),
+            (QueryError::Network(e), None) => tr!("multichain.network_retry", address, attempt, e),
+            (QueryError::Timeout, Some(chain)) => tr!("query.timeout_retry", address, chain, attempt, REQUEST_TIMEOUT_SECS),
+            (QueryError::Timeout, None) => tr!("multichain.timeout_retry", address, attempt, REQUEST_TIMEOUT_SECS),
+            (QueryError::Throttled(status, _), Some(chain)) => tr!("query.throttled_retry", address, chain, attempt, status),
+            (QueryError::Throttled(status, _), None) => tr!("multichain.throttled_retry", address, attempt, status),
+            (QueryError::Parse { error, .. }, Some(chain)) => tr!("query.parse_retry", address, chain, attempt, error),
+            (QueryError::Parse { error, .. }, None) => tr!("multichain.parse_retry", address, attempt, error),
+        }
+    }
+
+    fn failure_message(&self, address: &str) -> String {
+        match self {
+            QueryError::Network(e) => tr!("query.network_failed", address, e),
+            QueryError::Timeout => tr!("query.timeout_failed", address, REQUEST_TIMEOUT_SECS),
+            QueryError::Throttled(status, _) => tr!("query.throttled_failed", address, status),
+            QueryError::Parse { error, .. } => tr!("query.parse_failed", address, error),
+        }
+    }
+
+    fn save_body(&self, ctx: &QueryContext, address: &str, chain: &str) {
+        if let QueryError::Parse { body, .. } = self {
+            ctx.save_failed_response(address, chain, body);
+        }
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(%address, %chain))]
+async fn get_last_txs_single_chain(ctx: &QueryContext, address: &str, chain: &str) -> Option<QueryResult> {
+    let payload = RpcRequestSingle {
+        jsonrpc: "2.0",
+        method: "ankr_getTransactionsByAddress",
+        params: RpcParamsSingle {
+            blockchain: chain,
+            address,
+            desc_order: true,
+            page_size: 1,
+        },
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    let mut rechecked = false;
+    loop {
+        attempt += 1;
+        let started = Instant::now();
+        let error = match ctx.call::<_, RpcResult>(&ctx.base_url, &payload, payload.id).await {
+            Ok(json_body) => {
+                let latency = ctx.observe_latency(started);
+                if let Some(tx) = json_body.result.as_ref().and_then(|res| res.transactions.first()) {
+                    let result = QueryResult::found(address, chain, tx).with_latency(latency);
+                    info!("{}", tr!("query.found", address, chain_display_name(chain), short_hash(&result.tx_hash), result.tx_time));
+                    return Some(result);
+                }
+                // the recheck is a fresh query with its own retries
+                if let (false, Some(delay)) = (rechecked, ctx.confirm_empty_delay) {
+                    warn!("{}", tr!("query.rechecking", address, chain_display_name(chain)));
+                    rechecked = true;
+                    attempt = 0;
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+                info!("{}", tr!("query.no_transactions", address, chain_display_name(chain)));
+                return Some(QueryResult::empty(address, chain, QueryStatus::NoTransactions).with_latency(latency));
+            }
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", error.retry_message(address, Some(chain), attempt));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        error!("{}", error.failure_message(address));
+        error.save_body(ctx, address, chain);
+        return Some(QueryResult::empty(address, chain, error.status()).with_error(error.describe()));
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(%address, %chain))]
+async fn get_last_token_transfer(ctx: &QueryContext, address: &str, chain: &str) -> Option<QueryResult> {
+    let payload = RpcRequestSingle {
+        jsonrpc: "2.0",
+        method: "ankr_getTokenTransfers",
+        params: RpcParamsSingle {
+            blockchain: chain,
+            address,
+            desc_order: true,
+            page_size: 1,
+        },
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    let mut rechecked = false;
+    loop {
+        attempt += 1;
+        let started = Instant::now();
+        let error = match ctx.call::<_, TokenTransfersResult>(&ctx.base_url, &payload, payload.id).await {
+            Ok(json_body) => {
+                let latency = ctx.observe_latency(started);
+                if let Some(transfer) = json_body.result.as_ref().and_then(|res| res.transfers.first()) {
+                    let result = QueryResult::token_transfer(address, chain, transfer).with_latency(latency);
+                    let token = result.token_symbol.as_deref().or(result.token_contract.as_deref()).unwrap_or_default();
+                    info!("{}", tr!("query.token_found", address, chain_display_name(chain), token, short_hash(&result.tx_hash), result.tx_time));
+                    return Some(result);
+                }
+                // the recheck is a fresh query with its own retries
+                if let (false, Some(delay)) = (rechecked, ctx.confirm_empty_delay) {
+                    warn!("{}", tr!("query.rechecking", address, chain_display_name(chain)));
+                    rechecked = true;
+                    attempt = 0;
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+                info!("{}", tr!("query.no_token_transfers", address, chain_display_name(chain)));
+                return Some(QueryResult::empty(address, chain, QueryStatus::NoTransactions).with_latency(latency));
+            }
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", error.retry_message(address, Some(chain), attempt));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        error!("{}", error.failure_message(address));
+        error.save_body(ctx, address, chain);
+        return Some(QueryResult::empty(address, chain, error.status()).with_error(error.describe()));
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(%address, %chain))]
+async fn get_custom_result(ctx: &QueryContext, custom: &CustomMethod, address: &str, chain: &str) -> QueryResult {
+    let payload = RpcRequestCustom {
+        jsonrpc: "2.0",
+        method: &custom.method,
+        params: custom.params(address, chain),
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let started = Instant::now();
+        let error = match ctx.call::<_, serde_json::Value>(&ctx.base_url, &payload, payload.id).await {
+            Ok(json_body) => {
+                let latency = ctx.observe_latency(started);
+                if let Some(error) = &json_body.error {
+                    warn!("{}", tr!("query.custom_error", address, chain_display_name(chain), error.message));
+                    return QueryResult::empty(address, chain, QueryStatus::NoData).with_latency(latency).with_error(error.message.clone());
+                }
+                return match json_body.result.as_ref().and_then(|result| custom.extract(result)) {
+                    Some(value) => {
+                        info!("{}", tr!("query.custom_found", address, chain_display_name(chain), value.chars().take(80).collect::<String>()));
+                        QueryResult {
+                            tx_hash: String::new(),
+                            rpc_value: Some(value),
+                            ..QueryResult::empty(address, chain, QueryStatus::Found)
+                        }
+                        .with_latency(latency)
+                    }
+                    None => {
+                        info!("{}", tr!("query.custom_empty", address, chain_display_name(chain), custom.result_path));
+                        QueryResult::empty(address, chain, QueryStatus::NoTransactions).with_latency(latency)
+                    }
+                };
+            }
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", error.retry_message(address, Some(chain), attempt));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        error!("{}", error.failure_message(address));
+        error.save_body(ctx, address, chain);
+        return QueryResult::empty(address, chain, error.status()).with_error(error.describe());
+    }
+}
+
+// the oldest transaction is one more page_size:1 request with the order reversed; a failure only leaves the span empty
+#[tracing::instrument(level = "debug", skip_all, fields(%address, %chain))]
+async fn get_first_tx_epoch(ctx: &QueryContext, address: &str, chain: &str) -> Option<i64> {
+    let payload = RpcRequestSingle {
+        jsonrpc: "2.0",
+        method: "ankr_getTransactionsByAddress",
+        params: RpcParamsSingle {
+            blockchain: chain,
+            address,
+            desc_order: false,
+            page_size: 1,
+        },
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let error = match ctx.call::<_, RpcResult>(&ctx.base_url, &payload, payload.id).await {
+            Ok(json_body) => return json_body.result.as_ref().and_then(|res| res.transactions.first()).and_then(|tx| parse_timestamp(&tx.timestamp)),
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() && !ctx.is_shutting_down() {
+            warn!("{}", error.retry_message(address, Some(chain), attempt));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        warn!("{}", tr!("query.first_tx_failed", address, chain_display_name(chain), error.describe()));
+        error.save_body(ctx, address, chain);
+        return None;
+    }
+}
+
+// the secondary provider's answer for one address and chain; None when it is off, does not cover the chain or kept failing.
+// It runs outside the concurrency permits, paced by its own rate limit
+async fn secondary_lookup(ctx: &QueryContext, address: &str, chain: &str) -> Option<QueryResult> {
+    let secondary = ctx.secondary.as_ref()?;
+    etherscan::chain_id(chain)?;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        if ctx.is_shutting_down() {
+            return None;
+        }
+        if let Some(limiter) = &ctx.secondary_limiter {
+            limiter.until_ready().await;
+        }
+        ctx.requests.fetch_add(1, Ordering::Relaxed);
+        let client = ctx.clients.client(ctx.clients.pick());
+        let lookup = etherscan::last_transaction(client, &ctx.etherscan_url, &secondary.api_key, chain, address);
+        let error = match timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), lookup).await {
+            Ok(Ok(found)) => {
+                let result = match found {
+                    Some(tx) => QueryResult::found(address, chain, &tx),
+                    None => QueryResult::empty(address, chain, QueryStatus::NoTransactions),
+                };
+                return Some(QueryResult { provider: Some(secondary.name().to_string()), ..result });
+            }
+            Ok(Err(e)) => e,
+            Err(_) => etherscan::EtherscanError::Transient(tr!("error.timeout", REQUEST_TIMEOUT_SECS)),
+        };
+        if !error.is_transient() || attempt >= MAX_RETRIES {
+            warn!("{}", tr!("secondary.failed", secondary.name(), address, chain_display_name(chain), error));
+            return None;
+        }
+        tokio::time::sleep(ctx.retry_delay).await;
+    }
+}
+
+// like `preflight` for the primary provider: one lookup at startup so a bad key stops the run instead of failing every lookup
+async fn secondary_preflight(ctx: &QueryContext, secondary: &SecondaryProvider, chain: &str) -> Result<(), String> {
+    let client = ctx.clients.client(ctx.clients.pick());
+    let lookup = etherscan::last_transaction(client, &ctx.etherscan_url, &secondary.api_key, chain, PREFLIGHT_ADDRESS);
+    match timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), lookup).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(tr!("error.timeout", REQUEST_TIMEOUT_SECS)),
+    }
+}
+
+fn aggregate(ctx: &QueryContext, primary: QueryResult, secondary: Option<QueryResult>) -> QueryResult {
+    let Some(secondary_provider) = &ctx.secondary else {
+        return primary;
+    };
+    let primary = QueryResult { provider: Some(ctx.provider_name.to_string()), ..primary };
+    let merged = merge_provider_results(primary, secondary);
+    if merged.provider.as_deref() == Some(secondary_provider.name()) {
+        info!("{}", tr!("secondary.used", merged.address, chain_display_name(&merged.tx_chain), secondary_provider.name(), merged.tx_time));
+    }
+    merged
+}
+
+// callers hold the permits of the request that found the latest transaction
+async fn with_first_tx(ctx: &QueryContext, result: QueryResult) -> QueryResult {
+    if !ctx.query_span || result.status != QueryStatus::Found || result.tx_chain == SOLANA_CHAIN || ctx.is_shutting_down() {
+        return result;
+    }
+    let first_tx_epoch = get_first_tx_epoch(ctx, &result.address, &result.tx_chain).await;
+    QueryResult { first_tx_epoch, ..result }
+}
+
+// what the one-request-per-address-and-chain modes ask for
+#[derive(Clone, Copy)]
+enum SingleMethod {
+    Transactions,
+    // the newest ERC-20 transfer instead of the newest native transaction
+    TokenTransfers,
+    Custom,
+}
+
+async fn get_last_txs_single_mode(ctx: Arc<QueryContext>, addresses: &[String], chains: &[String], method: SingleMethod) -> Vec<QueryResult> {
+    let mut tasks = Vec::new();
+
+    for chain in chains {
+        for address in addresses {
+            if ctx.is_completed(address, chain) {
+                continue;
+            }
+            let ctx = ctx.clone();
+            let addr = address.clone();
+            let chain_name = chain.clone();
+
+            tasks.push(tokio::spawn(async move {
+                let primary = async {
+                    let _permit = ctx.acquire_for(&chain_name).await;
+                    if ctx.is_shutting_down() {
+                        return None;
+                    }
+                    match (method, &ctx.custom_method) {
+                        (SingleMethod::TokenTransfers, _) => get_last_token_transfer(&ctx, &addr, &chain_name).await,
+                        (SingleMethod::Custom, Some(custom)) => Some(get_custom_result(&ctx, custom, &addr, &chain_name).await),
+                        _ => match get_last_txs_single_chain(&ctx, &addr, &chain_name).await {
+                            Some(result) => Some(with_first_tx(&ctx, result).await),
+                            None => None,
+                        },
+                    }
+                };
+                let result = match method {
+                    // the secondary lookup waits on its own rate limit, not on the primary's permit
+                    SingleMethod::Transactions => match tokio::join!(primary, secondary_lookup(&ctx, &addr, &chain_name)) {
+                        (Some(result), secondary) => Some(aggregate(&ctx, result, secondary)),
+                        (None, _) => None,
+                    },
+                    _ => primary.await,
+                };
+                ctx.record(result.as_slice());
+                ctx.keep(result)
+            }));
+        }
+    }
+
+    join_all(tasks).await.into_iter().flatten().flatten().collect()
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(%address, chain = SOLANA_CHAIN))]
+async fn get_last_tx_solana(ctx: &QueryContext, address: &str) -> QueryResult {
+    let payload = SolanaRequest {
+        jsonrpc: "2.0",
+        method: "getSignaturesForAddress",
+        params: (address, SolanaParams { limit: 1 }),
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let started = Instant::now();
+        let error = match ctx.call::<_, Vec<SolanaSignature>>(&ctx.solana_url, &payload, payload.id).await {
+            Ok(body) => match body.result.as_ref().and_then(|sigs| sigs.first()) {
+                Some(sig) => {
+                    let tx = Transaction {
+                        hash: sig.signature.clone(),
+                        timestamp: sig.block_time.map(|ts| format!("0x{:x}", ts)).unwrap_or_default(),
+                        blockchain: SOLANA_CHAIN.to_string(),
+                        from: None,
+                        to: None,
+                        value: None,
+                    };
+                    let result = QueryResult::found(address, SOLANA_CHAIN, &tx).with_latency(ctx.observe_latency(started));
+                    info!("{}", tr!("solana.found", address, short_hash(&result.tx_hash), result.tx_time));
+                    return result;
+                }
+                None => {
+                    info!("{}", tr!("solana.no_transactions", address));
+                    return QueryResult::empty(address, SOLANA_CHAIN, QueryStatus::NoTransactions).with_latency(ctx.observe_latency(started));
+                }
+            },
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", tr!("solana.retry", address, attempt, error.describe()));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        error!("{}", tr!("solana.failed", address, error.describe()));
+        error.save_body(ctx, address, SOLANA_CHAIN);
+        return QueryResult::empty(address, SOLANA_CHAIN, error.status()).with_error(error.describe());
+    }
+}
+
+async fn get_last_txs_solana(ctx: Arc<QueryContext>, addresses: &[String]) -> Vec<QueryResult> {
+    let mut tasks = Vec::new();
+
+    for address in addresses {
+        if ctx.is_completed(address, SOLANA_CHAIN) {
+            continue;
+        }
+        let ctx = ctx.clone();
+        let addr = address.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let _permit = ctx.acquire_for(SOLANA_CHAIN).await;
+            if ctx.is_shutting_down() {
+                return None;
+            }
+            let result = get_last_tx_solana(&ctx, &addr).await;
+            ctx.record(std::slice::from_ref(&result));
+            ctx.keep(Some(result))
+        }));
+    }
+
+    join_all(tasks).await.into_iter().flatten().flatten().collect()
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(%address, %chain))]
+async fn get_account_type(ctx: &QueryContext, address: &str, chain: &str) -> Option<AccountType> {
+    if let Some(fixtures) = &ctx.fixtures {
+        return Some(fixtures.account_type(address));
+    }
+    let url = ctx.chain_rpc_url(chain);
+    let payload = RpcRequestGetCode {
+        jsonrpc: "2.0",
+        method: "eth_getCode",
+        params: (address, "latest"),
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let error = match ctx.call::<_, String>(&url, &payload, payload.id).await {
+            Ok(RpcResponse { result: Some(code), .. }) => {
+                let is_contract = !code.trim_start_matches("0x").is_empty();
+                return Some(if is_contract { AccountType::Contract } else { AccountType::Eoa });
+            }
+            // an RPC error answer is not going to change on a retry
+            Ok(_) => {
+                warn!("{}", tr!("account.classify_failed", address, chain_display_name(chain), t("error.missing_result")));
+                return None;
+            }
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        warn!("{}", tr!("account.classify_failed", address, chain_display_name(chain), error.describe()));
+        return None;
+    }
+}
+
+async fn preflight(ctx: &QueryContext, chain: &str) -> Result<(), String> {
+    let payload = RpcRequestSingle {
+        jsonrpc: "2.0",
+        method: "ankr_getTransactionsByAddress",
+        params: RpcParamsSingle {
+            blockchain: chain,
+            address: PREFLIGHT_ADDRESS,
+            desc_order: true,
+            page_size: 1,
+        },
+        id: next_request_id(),
+    };
+    let response = match ctx.post(&payload).await {
+        Ok(Ok(r)) => r,
+        Ok(Err(e)) => return Err(tr!("error.network", e)),
+        Err(_) => return Err(tr!("error.timeout", REQUEST_TIMEOUT_SECS)),
+    };
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(tr!("error.http_status", status, text.trim().chars().take(200).collect::<String>()));
+    }
+    let body: serde_json::Value = serde_json::from_str(&text).map_err(|e| tr!("error.parse", e))?;
+    if let Some(message) = body.pointer("/error/message").and_then(|m| m.as_str()) {
+        return Err(message.to_string());
+    }
+    parse_rpc_response(&text, payload.id)?.result.map(|_| ()).ok_or_else(|| t("error.missing_result").to_string())
+}
+
+async fn classify_accounts(ctx: Arc<QueryContext>, addresses: &[String], chain: &str) -> std::collections::HashMap<String, AccountType> {
+    let unique: HashSet<&String> = addresses.iter().collect();
+    let mut tasks = Vec::new();
+
+    for address in unique {
+        let ctx = ctx.clone();
+        let addr = address.clone();
+        let chain = chain.to_string();
+
+        tasks.push(tokio::spawn(async move {
+            let _permit = ctx.acquire_for(&chain).await;
+            get_account_type(&ctx, &addr, &chain).await.map(|account_type| (addr, account_type))
+        }));
+    }
+
+    join_all(tasks).await.into_iter().flatten().flatten().collect()
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(%address, %chain))]
+async fn confirm_no_transaction(ctx: &QueryContext, address: &str, chain: &str) -> QueryResult {
+    let key = (address.to_string(), chain.to_string());
+    if let Some(cached) = ctx.confirmed.lock().unwrap().get(&key) {
+        return cached.clone();
+    }
+    let result = fetch_no_transaction_confirmation(ctx, address, chain).await;
+    if !result.status.is_error() {
+        ctx.confirmed.lock().unwrap().insert(key, result.clone());
+    }
+    result
+}
+
+async fn fetch_no_transaction_confirmation(ctx: &QueryContext, address: &str, chain: &str) -> QueryResult {
+    let _permit = ctx.acquire_for(chain).await;
+    let payload = RpcRequestSingle {
+        jsonrpc: "2.0",
+        method: "ankr_getTransactionsByAddress",
+        params: RpcParamsSingle {
+            blockchain: chain,
+            address,
+            desc_order: true,
+            page_size: 1,
+        },
+        id: next_request_id(),
+    };
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let started = Instant::now();
+        let error = match ctx.call::<_, RpcResult>(&ctx.base_url, &payload, payload.id).await {
+            Ok(json_body) => {
+                let latency = ctx.observe_latency(started);
+                return match json_body.result.as_ref().and_then(|res| res.transactions.first()) {
+                    Some(tx) => QueryResult::found(address, chain, tx).with_latency(latency),
+                    None => QueryResult::empty(address, chain, QueryStatus::NoTransactions).with_latency(latency),
+                };
+            }
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", error.retry_message(address, Some(chain), attempt));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        error!("{}", error.failure_message(address));
+        error.save_body(ctx, address, chain);
+        return QueryResult::empty(address, chain, error.status()).with_error(error.describe());
+    }
+}
+
+async fn multichain_results(ctx: &QueryContext, addr: &str, chains: &[String], mut response: RpcResponse, mut latency: u64) -> Vec<QueryResult> {
+    let mut results = Vec::new();
+
+    // neither a result nor an error is more likely an API hiccup than an empty wallet, so it is asked once more
+    if response.result.is_none() && response.error.is_none() && !ctx.is_shutting_down() {
+        warn!("{}", tr!("query.empty_result_retry", addr));
+        tokio::time::sleep(ctx.retry_delay).await;
+        let retried = {
+            let _permit = ctx.concurrency.acquire().await;
+            query_address_multichain(ctx, addr, chains).await
+        };
+        match retried {
+            Ok((retried, retried_latency)) => (response, latency) = (retried, retried_latency),
+            Err(failed) => return failed,
+        }
+    }
+    let Some(res) = response.result else {
+        match &response.error {
+            Some(error) => warn!("{}", tr!("query.rpc_error", addr, error.message)),
+            None => warn!("{}", tr!("query.empty_result", addr)),
+        }
+        for chain in chains {
+            let result = QueryResult::empty(addr, chain, QueryStatus::NoData).with_latency(latency);
+            results.push(match &response.error {
+                Some(error) => result.with_error(error.message.clone()),
+                None => result,
+            });
+        }
+        return results;
+    };
+
+    let txs = res.transactions;
+    let mut by_chain: std::collections::HashMap<&str, &Transaction> = std::collections::HashMap::new();
+    for tx in &txs {
+        if !tx.hash.is_empty() {
+            by_chain.entry(tx.blockchain.as_str()).or_insert(tx);
+        }
+    }
+    for chain in chains {
+        if let Some(tx) = by_chain.get(chain.as_str()) {
+            let result = QueryResult::found(addr, chain, tx).with_latency(latency);
+            info!("{}", tr!("query.found", addr, chain_display_name(chain), short_hash(&result.tx_hash), result.tx_time));
+            results.push(result);
+            continue;
+        }
+
+        if ctx.skip_confirm {
+            info!("{}", tr!("query.no_transactions", addr, chain_display_name(chain)));
+            results.push(QueryResult::empty(addr, chain, QueryStatus::NoTransactions).with_latency(latency));
+            continue;
+        }
+
+        let result = confirm_no_transaction(ctx, addr, chain).await;
+        if result.status == QueryStatus::Found {
+            info!("{}", tr!("query.found", addr, chain_display_name(chain), short_hash(&result.tx_hash), result.tx_time));
+        } else if txs.is_empty() {
+            info!("{}", tr!("query.no_records_confirmed", addr, chain_display_name(chain)));
+        } else {
+            info!("{}", tr!("query.no_transactions_confirmed", addr, chain_display_name(chain)));
+        }
+        results.push(result);
+    }
+    if !ctx.query_span {
+        return results;
+    }
+    let mut spanned = Vec::with_capacity(results.len());
+    for result in results {
+        spanned.push(match result.status {
+            QueryStatus::Found => {
+                let _permit = ctx.acquire_for(&result.tx_chain).await;
+                with_first_tx(ctx, result).await
+            }
+            _ => result,
+        });
+    }
+    spanned
+}
+
+fn parse_rpc_response(text: &str, expected_id: u32) -> Result<RpcResponse, String> {
+    parse_rpc_body(text, expected_id)
+}
+
+fn parse_rpc_body<T: DeserializeOwned>(text: &str, expected_id: u32) -> Result<RpcResponse<T>, String> {
+    let body = serde_json::from_str::<RpcResponse<T>>(text).map_err(|e| e.to_string())?;
+    match body.id {
+        Some(id) if id == expected_id => Ok(body),
+        other => Err(tr!("error.id_mismatch", expected_id, format!("{:?}", other))),
+    }
+}
+
+fn multichain_request<'a>(addr: &'a str, chains: &'a [String], id: u32) -> RpcRequestMulti<'a> {
+    RpcRequestMulti {
+        jsonrpc: "2.0",
+        method: "ankr_getTransactionsByAddress",
+        params: RpcParamsMulti {
+            blockchain: chains.iter().map(|s| s.as_str()).collect(),
+            address: addr,
+            desc_order: true,
+            page_size: 30,
+            page_token: None,
+        },
+        id,
+    }
+}
+
+// only the multichain request itself; the follow-up confirmations in multichain_results take their own permits,
+// so callers can release theirs before running it
+#[tracing::instrument(level = "debug", skip_all, fields(address = %addr, chains = %chains.join(",")))]
+async fn query_address_multichain(ctx: &QueryContext, addr: &str, chains: &[String]) -> Result<(RpcResponse, u64), Vec<QueryResult>> {
+    let payload = multichain_request(addr, chains, next_request_id());
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let started = Instant::now();
+        let error = match ctx.call(&ctx.base_url, &payload, payload.id).await {
+            Ok(json_body) => return Ok((json_body, ctx.observe_latency(started))),
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", error.retry_message(addr, None, attempt));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        error!("{}", error.failure_message(addr));
+        error.save_body(ctx, addr, "multichain");
+        return Err(chains.iter().map(|chain| QueryResult::empty(addr, chain, error.status()).with_error(error.describe())).collect());
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(addresses = chunk.len()))]
+// responses may come back in any order, so each one is routed to its address by id; addresses left without one are queried again on their own
+async fn send_batch_request(ctx: &QueryContext, chunk: &[String], chains: &[String]) -> Option<(std::collections::HashMap<String, RpcResponse>, u64)> {
+    let payload: Vec<RpcRequestMulti> = chunk.iter().map(|addr| multichain_request(addr, chains, next_request_id())).collect();
+    let addresses_by_id: std::collections::HashMap<u32, &str> = payload.iter().map(|req| (req.id, req.params.address)).collect();
+
+    let mut attempt = 0;
+    let error = loop {
+        attempt += 1;
+        let started = Instant::now();
+        let response = match ctx.post(&payload).await {
+            Ok(Ok(r)) => r,
+            Ok(Err(e)) => break tr!("error.network", e),
+            Err(_) => break tr!("error.timeout", REQUEST_TIMEOUT_SECS),
+        };
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        // a throttled batch is retried whole with a growing delay; splitting it into one request per address
+        // would multiply the load on a provider that is already refusing requests
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            if attempt < MAX_RETRIES && !ctx.is_shutting_down() {
+                warn!("{}", tr!("batch.throttled_retry", chunk.len(), attempt, status));
+                tokio::time::sleep(ctx.retry_delay * attempt).await;
+                continue;
+            }
+            break tr!("error.http_status", status, text.trim().chars().take(200).collect::<String>());
+        }
+        match serde_json::from_str::<Vec<RpcResponse>>(&text) {
+            Ok(responses) => {
+                let mut routed = std::collections::HashMap::new();
+                for response in responses {
+                    match response.id.and_then(|id| addresses_by_id.get(&id)) {
+                        Some(addr) if routed.contains_key(*addr) => warn!("{}", tr!("batch.duplicate_id", addr)),
+                        Some(addr) => {
+                            routed.insert(addr.to_string(), response);
+                        }
+                        None => warn!("{}", tr!("batch.unknown_id", format!("{:?}", response.id))),
+                    }
+                }
+                return Some((routed, ctx.observe_latency(started)));
+            }
+            Err(e) => break tr!("error.parse", e),
+        }
+    };
+
+    warn!("{}", tr!("batch.failed", chunk.len(), error));
+    None
+}
+
+async fn get_last_txs_batch(ctx: Arc<QueryContext>, addresses: &[String], chains: Vec<String>) -> Vec<QueryResult> {
+    let pending: Vec<String> = addresses
+        .iter()
+        .filter(|addr| !chains.iter().all(|chain| ctx.is_completed(addr, chain)))
+        .cloned()
+        .collect();
+    let chains_arc = Arc::new(chains);
+    let batch_size = load_rpc_batch_size();
+    let mut tasks = Vec::new();
+
+    for chunk in pending.chunks(batch_size) {
+        let ctx = ctx.clone();
+        let chunk = chunk.to_vec();
+        let chains_arc = chains_arc.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let mut responses = if chunk.len() > 1 {
+                let _permit = ctx.concurrency.acquire().await;
+                if ctx.is_shutting_down() {
+                    return Vec::new();
+                }
+                send_batch_request(&ctx, &chunk, &chains_arc).await
+            } else {
+                None
+            };
+            let batch_failed = responses.is_none();
+            let batch_latency = responses.as_ref().map(|(_, latency)| *latency).unwrap_or_default();
+
+            let per_address: Vec<_> = chunk
+                .iter()
+                .map(|addr| {
+                    let response = responses.as_mut().and_then(|(routed, _)| routed.remove(addr));
+                    let (ctx, chains) = (&ctx, &chains_arc);
+                    async move {
+                        let results = match response {
+                            Some(resp) => multichain_results(ctx, addr, chains, resp, batch_latency).await,
+                            None => {
+                                if !batch_failed {
+                                    warn!("{}", tr!("batch.missing", addr));
+                                }
+                                let fetched = {
+                                    let _permit = ctx.concurrency.acquire().await;
+                                    if ctx.is_shutting_down() {
+                                        return Vec::new();
+                                    }
+                                    query_address_multichain(ctx, addr, chains).await
+                                };
+                                match fetched {
+                                    Ok((response, latency)) => multichain_results(ctx, addr, chains, response, latency).await,
+                                    Err(failed) => failed,
+                                }
+                            }
+                        };
+                        let results = match ctx.secondary {
+                            Some(_) => {
+                                let lookups = results.iter().map(|r| secondary_lookup(ctx, &r.address, &r.tx_chain));
+                                let secondary = join_all(lookups).await;
+                                results.into_iter().zip(secondary).map(|(primary, secondary)| aggregate(ctx, primary, secondary)).collect()
+                            }
+                            None => results,
+                        };
+                        ctx.record(&results);
+                        ctx.keep(results)
+                    }
+                })
+                .collect();
+
+            join_all(per_address).await.into_iter().flatten().collect::<Vec<_>>()
+        }));
+    }
+
+    let all_results = join_all(tasks).await;
+
+    let mut query_results = Vec::new();
+    for data_vec in all_results.into_iter().flatten() {
+        query_results.extend(data_vec);
+    }
+
+    query_results
+}
+
+struct ActivityRow {
+    address: String,
+    months: std::collections::BTreeMap<String, usize>,
+    // lowercase target contracts this wallet sent a transaction to
+    interacted: HashSet<String>,
+    truncated: bool,
+}
+
+impl ActivityRow {
+    // a contract missing from a truncated history may still have been called in an older page
+    fn interacted_with(&self, contract: &str) -> Option<bool> {
+        if self.interacted.contains(contract) {
+            Some(true)
+        } else if self.truncated {
+            None
+        } else {
+            Some(false)
+        }
+    }
+}
+
+// paging through the full transaction history, either for the monthly activity matrix (FETCH_ALL)
+// or to look for calls to TARGET_CONTRACT
+struct HistoryPlan {
+    max_pages: u32,
+    months: bool,
+    contracts: Vec<String>,
+}
+
+fn load_target_contracts() -> Result<Vec<String>> {
+    let mut contracts = Vec::new();
+    for value in std::env::var("TARGET_CONTRACT").unwrap_or_default().split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        if !is_hex_address(value) {
+            anyhow::bail!(tr!("config.invalid_target_contract", value));
+        }
+        let contract = value.to_lowercase();
+        if !contracts.contains(&contract) {
+            contracts.push(contract);
+        }
+    }
+    Ok(contracts)
+}
+
+fn load_history_plan() -> Result<Option<HistoryPlan>> {
+    let months = std::env::var("FETCH_ALL").map(|v| v == "1").unwrap_or(false);
+    let contracts = load_target_contracts()?;
+    if !months && contracts.is_empty() {
+        return Ok(None);
+    }
+    let max_pages = std::env::var("FETCH_ALL_MAX_PAGES").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_ACTIVITY_MAX_PAGES);
+    Ok(Some(HistoryPlan { max_pages: max_pages.max(1), months, contracts }))
+}
+
+async fn fetch_activity_page(ctx: &QueryContext, payload: &RpcRequestMulti<'_>) -> Option<RpcResult> {
+    if let Some(fixtures) = &ctx.fixtures {
+        return Some(fixtures.history(payload.params.address, &payload.params.blockchain));
+    }
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let error = match ctx.call(&ctx.base_url, payload, payload.id).await {
+            Ok(body) => return Some(body.result.unwrap_or(RpcResult { next_page_token: None, transactions: Vec::new() })),
+            Err(error) => error,
+        };
+        if attempt < error.max_attempts() {
+            warn!("{}", tr!("activity.retry", payload.params.address, attempt, error.describe()));
+            tokio::time::sleep(ctx.retry_delay).await;
+            continue;
+        }
+        warn!("{}", tr!("activity.failed", payload.params.address, error.describe()));
+        return None;
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(address = %addr))]
+async fn fetch_activity(ctx: &QueryContext, addr: &str, chains: &[String], plan: &HistoryPlan) -> ActivityRow {
+    let mut row = ActivityRow { address: addr.to_string(), months: std::collections::BTreeMap::new(), interacted: HashSet::new(), truncated: false };
+    let mut page_token = None;
+    for page in 1..=plan.max_pages {
+        let mut payload = multichain_request(addr, chains, next_request_id());
+        payload.params.page_size = ACTIVITY_PAGE_SIZE;
+        payload.params.page_token = page_token.take();
+        let Some(result) = fetch_activity_page(ctx, &payload).await else {
+            row.truncated = true;
+            break;
+        };
+        for tx in &result.transactions {
+            if let Some(month) = parse_timestamp(&tx.timestamp).and_then(|ts| format_epoch_as(ts, "%Y-%m")) {
+                *row.months.entry(month).or_default() += 1;
+            }
+            let sent = tx.from.as_deref().is_some_and(|from| from.eq_ignore_ascii_case(addr));
+            if let Some(to) = tx.to.as_deref().map(str::to_lowercase).filter(|to| sent && plan.contracts.contains(to)) {
+                row.interacted.insert(to);
+            }
+        }
+        // without the activity matrix there is nothing left to learn once every contract was seen
+        if !plan.months && row.interacted.len() == plan.contracts.len() {
+            break;
+        }
+        match result.next_page_token.filter(|token| !token.is_empty()) {
+            Some(token) if page < plan.max_pages => page_token = Some(token),
+            Some(_) => row.truncated = true,
+            None => break,
+        }
+    }
+    row
+}
+
+async fn collect_activity(ctx: Arc<QueryContext>, addresses: &[String], chains: &[String], plan: Arc<HistoryPlan>) -> Vec<ActivityRow> {
+    let chains = Arc::new(chains.to_vec());
+    let mut tasks = Vec::new();
+
+    for address in addresses {
+        let ctx = ctx.clone();
+        let chains = chains.clone();
+        let plan = plan.clone();
+        let addr = address.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let _permit = ctx.concurrency.acquire().await;
+            fetch_activity(&ctx, &addr, &chains, &plan).await
+        }));
+    }
+
+    join_all(tasks).await.into_iter().flatten().collect()
+}
+
+// every month from the earliest to the latest seen, so gaps show up as empty columns
+fn activity_months(rows: &[ActivityRow]) -> Vec<String> {
+    let parse = |month: &str| -> Option<(i32, u32)> {
+        let (year, month) = month.split_once('-')?;
+        Some((year.parse().ok()?, month.parse().ok()?))
+    };
+    let seen: Vec<(i32, u32)> = rows.iter().flat_map(|row| row.months.keys()).filter_map(|m| parse(m)).collect();
+    let (Some(&first), Some(&last)) = (seen.iter().min(), seen.iter().max()) else {
+        return Vec::new();
+    };
+
+    let mut months = Vec::new();
+    let (mut year, mut month) = first;
+    while (year, month) <= last {
+        months.push(format!("{:04}-{:02}", year, month));
+        (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    }
+    months
+}
+
+fn write_activity_sheet(workbook: &mut Workbook, rows: &[ActivityRow], formats: &SheetFormats) -> Result<()> {
+    let months = activity_months(rows);
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.activity"))?;
+    worksheet.write_string_with_format(0, 0, t("header.wallet_address"), &formats.header)?;
+    worksheet.set_column_width(0, 45)?;
+    for (i, month) in months.iter().enumerate() {
+        worksheet.write_string_with_format(0, (i + 1) as u16, month, &formats.header)?;
+        worksheet.set_column_width((i + 1) as u16, 9)?;
+    }
+    worksheet.set_freeze_panes(1, 1)?;
+
+    for (r, row) in rows.iter().enumerate() {
+        let row_idx = (r + 1) as u32;
+        worksheet.write_string(row_idx, 0, &row.address)?;
+        for (i, month) in months.iter().enumerate() {
+            if let Some(&count) = row.months.get(month) {
+                worksheet.write_number(row_idx, (i + 1) as u16, count as f64)?;
+            }
+        }
+    }
+    if !rows.is_empty() && !months.is_empty() {
+        let scale = ConditionalFormat2ColorScale::new().set_minimum_color(Color::White).set_maximum_color(Color::RGB(0x63BE7B));
+        worksheet.add_conditional_format(1, 1, rows.len() as u32, months.len() as u16, &scale)?;
+    }
+    Ok(())
+}
+
+fn contract_header(contract: &str) -> String {
+    tr!("header.interacted", contract)
+}
+
+fn write_contract_sheet(workbook: &mut Workbook, rows: &[ActivityRow], contracts: &[String], formats: &SheetFormats) -> Result<()> {
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.contracts"))?;
+    worksheet.write_string_with_format(0, 0, t("header.wallet_address"), &formats.header)?;
+    worksheet.set_column_width(0, 45)?;
+    for (i, contract) in contracts.iter().enumerate() {
+        worksheet.write_string_with_format(0, (i + 1) as u16, contract_header(contract), &formats.header)?;
+        worksheet.set_column_width((i + 1) as u16, 20)?;
+    }
+    worksheet.set_freeze_panes(1, 1)?;
+    worksheet.autofilter(0, 0, rows.len() as u32, contracts.len() as u16)?;
+
+    for (r, row) in rows.iter().enumerate() {
+        let row_idx = (r + 1) as u32;
+        worksheet.write_string(row_idx, 0, &row.address)?;
+        for (i, contract) in contracts.iter().enumerate() {
+            if let Some(interacted) = row.interacted_with(contract) {
+                let format = if interacted { &formats.active } else { &formats.plain };
+                worksheet.write_boolean_with_format(row_idx, (i + 1) as u16, interacted, format)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_contract_csv(path: &Path, rows: &[ActivityRow], contracts: &[String]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(std::iter::once(t("header.address").to_string()).chain(contracts.iter().map(|c| contract_header(c))))?;
+    for row in rows {
+        let cells = contracts.iter().map(|contract| row.interacted_with(contract).map(|b| b.to_string()).unwrap_or_default());
+        writer.write_record(std::iter::once(row.address.clone()).chain(cells))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_activity_csv(path: &Path, rows: &[ActivityRow]) -> Result<()> {
+    let months = activity_months(rows);
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(std::iter::once(t("header.address")).chain(months.iter().map(String::as_str)))?;
+    for row in rows {
+        let counts = months.iter().map(|month| row.months.get(month).copied().unwrap_or_default().to_string());
+        writer.write_record(std::iter::once(row.address.clone()).chain(counts))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+struct SheetFormats {
+    header: Format,
+    active: Format,
+    inactive: Format,
+    plain: Format,
+}
+
+impl SheetFormats {
+    fn new() -> Self {
+        SheetFormats {
+            header: Format::new().set_bold(),
+            active: Format::new().set_background_color(Color::RGB(0xC6EFCE)),
+            inactive: Format::new().set_background_color(Color::RGB(0xFFC7CE)),
+            plain: Format::new(),
+        }
+    }
+
+    fn for_status(&self, status: QueryStatus) -> &Format {
+        match status {
+            QueryStatus::Found => &self.active,
+            QueryStatus::NoTransactions => &self.inactive,
+            _ => &self.plain,
+        }
+    }
+}
+
+fn write_hash_cell(worksheet: &mut Worksheet, row_idx: u32, col: u16, row: &QueryResult, format: &Format) -> Result<()> {
+    match explorers().tx_url(&row.tx_chain, &row.tx_hash).filter(|_| row.status == QueryStatus::Found) {
+        Some(url) => {
+            let url = Url::new(url).set_text(&row.tx_hash);
+            worksheet.write_url_with_format(row_idx, col, url, format)?;
+        }
+        None => {
+            worksheet.write_string_with_format(row_idx, col, placeholders().hash(row), format)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_address_cell(worksheet: &mut Worksheet, row_idx: u32, col: u16, row: &QueryResult, format: &Format) -> Result<()> {
+    match explorers().address_url(&row.tx_chain, &row.address) {
+        Some(url) => {
+            worksheet.write_url_with_format(row_idx, col, Url::new(url).set_text(&row.address), format)?;
+        }
+        None => {
+            worksheet.write_string_with_format(row_idx, col, &row.address, format)?;
+        }
+    }
+    Ok(())
+}
+
+// one output column; OUTPUT_COLUMNS selects and orders these by name
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Column {
+    Chain,
+    Address,
+    Label,
+    AccountType,
+    Latency,
+    Direction,
+    From,
+    To,
+    Value,
+    Time,
+    Hash,
+    Status,
+    Dormant,
+    FirstTx,
+    Span,
+    Token,
+    TokenContract,
+    Result,
+    Provider,
+}
+
+const COLUMN_NAMES: [(&str, Column); 19] = [
+    ("chain", Column::Chain),
+    ("address", Column::Address),
+    ("label", Column::Label),
+    ("account_type", Column::AccountType),
+    ("latency", Column::Latency),
+    ("direction", Column::Direction),
+    ("from", Column::From),
+    ("to", Column::To),
+    ("value", Column::Value),
+    ("time", Column::Time),
+    ("hash", Column::Hash),
+    ("status", Column::Status),
+    ("dormant", Column::Dormant),
+    ("first_tx", Column::FirstTx),
+    ("span_days", Column::Span),
+    ("token", Column::Token),
+    ("token_contract", Column::TokenContract),
+    ("result", Column::Result),
+    ("provider", Column::Provider),
+];
+
+const DETAIL_COLUMNS: [Column; 4] = [Column::Direction, Column::From, Column::To, Column::Value];
+
+impl Column {
+    fn name(self) -> &'static str {
+        COLUMN_NAMES.iter().find(|(_, column)| *column == self).map(|(name, _)| *name).unwrap_or_default()
+    }
+
+    // chain sheets keep their longer headers; the flat sheet and CSVs use the short ones --retry-failed reads
+    fn header(self, per_chain: bool) -> String {
+        match self {
+            Column::Chain => t("header.chain").to_string(),
+            Column::Address if per_chain => t("header.wallet_address").to_string(),
+            Column::Address => t("header.address").to_string(),
+            Column::Label => t("header.label").to_string(),
+            Column::AccountType => t("header.account_type").to_string(),
+            Column::Latency => t("header.latency").to_string(),
+            Column::Direction => t("header.direction").to_string(),
+            Column::From => t("header.from").to_string(),
+            Column::To => t("header.to").to_string(),
+            Column::Value => t("header.value").to_string(),
+            Column::Time if per_chain => tr!("header.last_tx_time", TIME_SETTINGS.get_or_init(TimeSettings::default).zone.label()),
+            Column::Time => t("header.time").to_string(),
+            Column::Hash if per_chain => t("header.tx_hash").to_string(),
+            Column::Hash => "Hash".to_string(),
+            Column::Status => t("header.status").to_string(),
+            Column::Dormant => t("header.dormant").to_string(),
+            Column::FirstTx if per_chain => tr!("header.first_tx_time", TIME_SETTINGS.get_or_init(TimeSettings::default).zone.label()),
+            Column::FirstTx => t("header.first_tx").to_string(),
+            Column::Span => t("header.span_days").to_string(),
+            Column::Token => t("header.token").to_string(),
+            Column::TokenContract => t("header.token_contract").to_string(),
+            Column::Result => t("header.result").to_string(),
+            Column::Provider => t("header.provider").to_string(),
+        }
+    }
+
+    fn width(self) -> f64 {
+        match self {
+            Column::Address | Column::From | Column::To | Column::TokenContract => 45.0,
+            Column::Hash | Column::Result => 70.0,
+            Column::Time | Column::FirstTx => 25.0,
+            Column::Label => 20.0,
+            Column::Value => 15.0,
+            Column::Latency | Column::Direction => 10.0,
+            Column::Chain | Column::AccountType | Column::Status | Column::Dormant | Column::Span | Column::Token | Column::Provider => 12.0,
+        }
+    }
+
+    fn value(self, row: &QueryResult, inactive_days: Option<i64>) -> String {
+        match self {
+            Column::Chain => row.tx_chain.clone(),
+            Column::Address => row.address.clone(),
+            Column::Label => row.label.clone().unwrap_or_default(),
+            Column::AccountType => row.account_type.map(AccountType::label).unwrap_or_default().to_string(),
+            Column::Latency => row.latency_ms.map(|ms| ms.to_string()).unwrap_or_default(),
+            Column::Direction => row.direction().map(Direction::label).unwrap_or_default().to_string(),
+            Column::From => row.tx_from.clone().unwrap_or_default(),
+            Column::To => row.tx_to.clone().unwrap_or_default(),
+            Column::Value => row.tx_value.clone().unwrap_or_default(),
+            Column::Time => placeholders().time(row).to_string(),
+            Column::Hash => placeholders().hash(row).to_string(),
+            Column::Status => row.status.label().to_string(),
+            Column::Dormant => inactive_days.and_then(|days| is_dormant(row, days)).map(|d| d.to_string()).unwrap_or_default(),
+            Column::FirstTx => row.first_tx_epoch.map(format_epoch).unwrap_or_default(),
+            Column::Span => row.span_days().map(|days| days.to_string()).unwrap_or_default(),
+            Column::Token => row.token_symbol.clone().unwrap_or_default(),
+            Column::TokenContract => row.token_contract.clone().unwrap_or_default(),
+            Column::Result => row.rpc_value.clone().unwrap_or_default(),
+            Column::Provider => row.provider.clone().unwrap_or_default(),
+        }
+    }
+
+    fn write_cell(self, worksheet: &mut Worksheet, row_idx: u32, col: u16, row: &QueryResult, inactive_days: Option<i64>, format: &Format) -> Result<()> {
+        match self {
+            Column::Chain => {
+                worksheet.write_string_with_format(row_idx, col, chain_display_name(&row.tx_chain), format)?;
+            }
+            Column::Address => write_address_cell(worksheet, row_idx, col, row, format)?,
+            Column::Hash => write_hash_cell(worksheet, row_idx, col, row, format)?,
+            Column::Latency => match row.latency_ms {
+                Some(ms) => {
+                    worksheet.write_number_with_format(row_idx, col, ms as f64, format)?;
+                }
+                None => {
+                    worksheet.write_blank(row_idx, col, format)?;
+                }
+            },
+            Column::Value => match row.tx_value.as_deref().and_then(|v| v.parse::<f64>().ok()) {
+                Some(value) => {
+                    worksheet.write_number_with_format(row_idx, col, value, format)?;
+                }
+                None => {
+                    worksheet.write_blank(row_idx, col, format)?;
+                }
+            },
+            Column::Span => match row.span_days() {
+                Some(days) => {
+                    worksheet.write_number_with_format(row_idx, col, days as f64, format)?;
+                }
+                None => {
+                    worksheet.write_blank(row_idx, col, format)?;
+                }
+            },
+            Column::Dormant => {
+                if let Some(dormant) = inactive_days.and_then(|days| is_dormant(row, days)) {
+                    worksheet.write_boolean_with_format(row_idx, col, dormant, format)?;
+                }
+            }
+            _ => {
+                worksheet.write_string_with_format(row_idx, col, self.value(row, inactive_days), format)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// which of the data-dependent columns this run actually has values for
+#[derive(Clone, Copy)]
+struct ColumnData {
+    label: bool,
+    account_type: bool,
+    dormant: bool,
+    span: bool,
+    token: bool,
+    result: bool,
+    provider: bool,
+}
+
+impl ColumnData {
+    fn new(grouped: &std::collections::HashMap<String, Vec<&QueryResult>>, inactive_days: Option<i64>) -> Self {
+        ColumnData {
+            label: grouped.values().flatten().any(|r| r.label.is_some()),
+            account_type: grouped.values().flatten().any(|r| r.account_type.is_some()),
+            dormant: inactive_days.is_some(),
+            span: grouped.values().flatten().any(|r| r.first_tx_epoch.is_some()),
+            token: grouped.values().flatten().any(|r| r.token_contract.is_some()),
+            result: grouped.values().flatten().any(|r| r.rpc_value.is_some()),
+            provider: grouped.values().flatten().any(|r| r.provider.is_some()),
+        }
+    }
+
+    fn has(&self, column: Column) -> bool {
+        match column {
+            Column::Label => self.label,
+            Column::AccountType => self.account_type,
+            Column::Dormant => self.dormant,
+            Column::FirstTx | Column::Span => self.span,
+            Column::Token | Column::TokenContract => self.token,
+            Column::Result => self.result,
+            Column::Provider => self.provider,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct OptionalColumns {
+    latency: bool,
+    details: bool,
+    selected: Option<Vec<Column>>,
+}
+
+fn load_optional_columns() -> OptionalColumns {
+    let enabled = |key: &str| std::env::var(key).map(|v| v == "1").unwrap_or(false);
+    OptionalColumns { latency: enabled("LATENCY_COLUMN"), details: enabled("INCLUDE_DETAILS"), selected: load_output_columns() }
+}
+
+fn load_output_columns() -> Option<Vec<Column>> {
+    let value = std::env::var("OUTPUT_COLUMNS").ok().filter(|v| !v.trim().is_empty())?;
+    let mut columns = Vec::new();
+    for name in value.split(',').map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()) {
+        match COLUMN_NAMES.iter().find(|(known, _)| *known == name) {
+            Some((_, column)) if !columns.contains(column) => columns.push(*column),
+            Some(_) => {}
+            None => {
+                let known: Vec<&str> = COLUMN_NAMES.iter().map(|(known, _)| *known).collect();
+                warn!("{}", tr!("output.unknown_column", name, known.join(",")));
+            }
+        }
+    }
+    if columns.is_empty() {
+        warn!("{}", t("output.no_columns"));
+        return None;
+    }
+    Some(columns)
+}
+
+impl OptionalColumns {
+    // LATENCY_COLUMN / INCLUDE_DETAILS columns, placed after the address by default
+    fn extras(&self, data: ColumnData) -> Vec<Column> {
+        let mut columns: Vec<Column> = [Column::Label, Column::AccountType, Column::Token, Column::TokenContract].into_iter().filter(|c| data.has(*c)).collect();
+        columns.extend(self.latency.then_some(Column::Latency));
+        if self.details {
+            columns.extend(DETAIL_COLUMNS);
+        }
+        columns
+    }
+
+    // OUTPUT_COLUMNS replaces the default layout; columns without data in this run are left out
+    fn pick(&self, default: Vec<Column>, data: ColumnData) -> Vec<Column> {
+        match &self.selected {
+            Some(selected) => selected.iter().copied().filter(|c| data.has(*c)).collect(),
+            None => default,
+        }
+    }
+
+    fn sheet(&self, leading: &[Column], data: ColumnData) -> Vec<Column> {
+        let mut columns = leading.to_vec();
+        columns.extend(self.extras(data));
+        // a custom method has no transaction to show, only the extracted value
+        if data.result {
+            columns.push(Column::Result);
+            return self.pick(columns, data);
+        }
+        columns.extend(data.span.then_some(Column::FirstTx));
+        columns.push(Column::Time);
+        columns.extend(data.span.then_some(Column::Span));
+        columns.push(Column::Hash);
+        columns.extend(data.provider.then_some(Column::Provider));
+        columns.extend(data.dormant.then_some(Column::Dormant));
+        self.pick(columns, data)
+    }
+
+    fn csv(&self, data: ColumnData) -> Vec<Column> {
+        let mut columns = vec![Column::Chain, Column::Address];
+        columns.extend([Column::Label, Column::AccountType, Column::Token, Column::TokenContract].into_iter().filter(|c| data.has(*c)));
+        if data.result {
+            columns.extend([Column::Result, Column::Status]);
+            return self.pick(columns, data);
+        }
+        columns.extend([Column::Time, Column::Hash, Column::Status]);
+        columns.extend(data.provider.then_some(Column::Provider));
+        columns.extend(data.dormant.then_some(Column::Dormant));
+        if data.span {
+            columns.extend([Column::FirstTx, Column::Span]);
+        }
+        columns.extend(self.latency.then_some(Column::Latency));
+        if self.details {
+            columns.extend(DETAIL_COLUMNS);
+        }
+        self.pick(columns, data)
+    }
+
+    // warned once per run rather than once per writer
+    fn warn_missing(&self, data: ColumnData) {
+        let missing: Vec<&str> = self.selected.iter().flatten().filter(|c| !data.has(**c)).map(|c| c.name()).collect();
+        if !missing.is_empty() {
+            warn!("{}", tr!("output.columns_unavailable", missing.join(",")));
+        }
+    }
+}
+
+fn write_column_headers(worksheet: &mut Worksheet, col: u16, columns: &[Column], per_chain: bool, formats: &SheetFormats) -> Result<()> {
+    for (i, column) in columns.iter().enumerate() {
+        let col = col + i as u16;
+        worksheet.write_string_with_format(0, col, column.header(per_chain), &formats.header)?;
+        worksheet.set_column_width(col, column.width())?;
+    }
+    Ok(())
+}
+
+fn write_column_cells(
+    worksheet: &mut Worksheet,
+    row_idx: u32,
+    col: u16,
+    columns: &[Column],
+    row: &QueryResult,
+    inactive_days: Option<i64>,
+    format: &Format,
+) -> Result<()> {
+    for (i, column) in columns.iter().enumerate() {
+        column.write_cell(worksheet, row_idx, col + i as u16, row, inactive_days, format)?;
+    }
+    Ok(())
+}
+
+// provider chain ids are not under our control, so strip what Excel rejects in sheet names
+fn sheet_name(name: &str) -> String {
+    name.chars().map(|c| if "[]:*?/\\".contains(c) { '_' } else { c }).take(MAX_SHEET_NAME_CHARS).collect()
+}
+
+// a workbook open in Excel is locked on Windows; rather than losing the run, dump the results to JSON and retry under another name
+fn save_workbook(workbook: &mut Workbook, filename: &str, results: &[QueryResult]) -> Result<String> {
+    let error = match workbook.save(filename) {
+        Ok(()) => return Ok(filename.to_string()),
+        Err(e) => e,
+    };
+    warn!("{}", tr!("output.save_failed", filename, error));
+    let stem = format!("{}_{}", filename.trim_end_matches(".xlsx"), Local::now().format("%Y%m%d-%H%M%S"));
+    let sidecar = format!("{}.json", stem);
+    match File::create(&sidecar).map_err(anyhow::Error::from).and_then(|file| Ok(serde_json::to_writer(io::BufWriter::new(file), results)?)) {
+        Ok(()) => warn!("{}", tr!("output.sidecar_saved", results.len(), sidecar)),
+        Err(e) => error!("{}", tr!("output.sidecar_failed", sidecar, e)),
+    }
+    let alternate = format!("{}.xlsx", stem);
+    match workbook.save(&alternate) {
+        Ok(()) => {
+            warn!("{}", tr!("output.saved_alternate", alternate));
+            Ok(alternate)
+        }
+        Err(e) => anyhow::bail!(tr!("output.save_gave_up", alternate, e, sidecar)),
+    }
+}
+
+fn write_chain_sheets(
+    workbook: &mut Workbook,
+    grouped: &std::collections::HashMap<String, Vec<&QueryResult>>,
+    target_chains: &[String],
+    inactive_days: Option<i64>,
+    optional: &OptionalColumns,
+    formats: &SheetFormats,
+) -> Result<()> {
+    let columns = optional.sheet(&[Column::Address], ColumnData::new(grouped, inactive_days));
+
+    for chain in target_chains {
+        if let Some(rows) = grouped.get(chain) {
+            let worksheet = workbook.add_worksheet().set_name(sheet_name(chain_display_name(chain)))?;
+
+            write_column_headers(worksheet, 0, &columns, true, formats)?;
+            worksheet.set_freeze_panes(1, 0)?;
+            worksheet.autofilter(0, 0, rows.len() as u32, columns.len().saturating_sub(1) as u16)?;
+
+            for (i, row) in rows.iter().enumerate() {
+                write_column_cells(worksheet, (i + 1) as u32, 0, &columns, row, inactive_days, formats.for_status(row.status))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn csv_header(columns: &[Column]) -> Vec<String> {
+    columns.iter().map(|column| column.header(false)).collect()
+}
+
+fn csv_record(columns: &[Column], row: &QueryResult, inactive_days: Option<i64>) -> Vec<String> {
+    columns.iter().map(|column| column.value(row, inactive_days)).collect()
+}
+
+fn write_chain_csvs(
+    dir: &Path,
+    grouped: &std::collections::HashMap<String, Vec<&QueryResult>>,
+    target_chains: &[String],
+    inactive_days: Option<i64>,
+    optional: &OptionalColumns,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let columns = optional.csv(ColumnData::new(grouped, inactive_days));
+
+    for chain in target_chains {
+        let Some(rows) = grouped.get(chain) else {
+            continue;
+        };
+        let mut writer = csv::Writer::from_path(dir.join(format!("{}.csv", sheet_name(chain))))?;
+        writer.write_record(csv_header(&columns))?;
+        for row in rows {
+            writer.write_record(csv_record(&columns, row, inactive_days))?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct RunTally {
+    rows: usize,
+    statuses: std::collections::HashMap<QueryStatus, usize>,
+    // address -> (any chain found, every chain empty)
+    addresses: std::collections::HashMap<String, (bool, bool)>,
+}
+
+impl RunTally {
+    fn from_results(results: &[QueryResult]) -> Self {
+        let mut tally = RunTally::default();
+        results.iter().for_each(|row| tally.add(row));
+        tally
+    }
+
+    fn add(&mut self, row: &QueryResult) {
+        self.rows += 1;
+        *self.statuses.entry(row.status).or_default() += 1;
+        let entry = self.addresses.entry(row.address.clone()).or_insert((false, true));
+        entry.0 |= row.status == QueryStatus::Found;
+        entry.1 &= row.status == QueryStatus::NoTransactions;
+    }
+
+    fn count(&self, status: QueryStatus) -> usize {
+        self.statuses.get(&status).copied().unwrap_or_default()
+    }
+
+    fn errors(&self) -> usize {
+        QueryStatus::ERRORS.iter().map(|&status| self.count(status)).sum()
+    }
+
+    fn active(&self) -> usize {
+        self.addresses.values().filter(|(found, _)| *found).count()
+    }
+
+    fn inactive(&self) -> usize {
+        self.addresses.values().filter(|(_, empty)| *empty).count()
+    }
+}
+
+struct StreamOutput {
+    csv: Option<csv::Writer<File>>,
+    jsonl: Option<io::BufWriter<File>>,
+    columns: Vec<Column>,
+    inactive_days: Option<i64>,
+    labels: std::collections::HashMap<String, String>,
+    account_types: std::collections::HashMap<String, AccountType>,
+    exclude_contracts: bool,
+    inactive_only: bool,
+    filter: Option<ResultFilter>,
+    tally: RunTally,
+}
+
+impl StreamOutput {
+    fn write(&mut self, results: &[QueryResult]) {
+        if let Err(e) = self.try_write(results) {
+            warn!("{}", tr!("output.stream_write_failed", e));
+        }
+    }
+
+    fn try_write(&mut self, results: &[QueryResult]) -> Result<()> {
+        for result in results {
+            let mut row = result.clone();
+            row.label = self.labels.get(&row.address).cloned();
+            row.account_type = self.account_types.get(&row.address).copied();
+            if self.exclude_contracts && row.account_type == Some(AccountType::Contract) {
+                continue;
+            }
+            self.tally.add(&row);
+            if self.inactive_only && self.inactive_days.and_then(|days| is_dormant(&row, days)) != Some(true) {
+                continue;
+            }
+            if self.filter.is_some_and(|filter| !filter.keeps(&row)) {
+                continue;
+            }
+            if let Some(writer) = &mut self.csv {
+                writer.write_record(csv_record(&self.columns, &row, self.inactive_days))?;
+            }
+            if let Some(writer) = &mut self.jsonl {
+                serde_json::to_writer(&mut *writer, &row)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        if let Some(writer) = &mut self.csv {
+            writer.flush()?;
+        }
+        if let Some(writer) = &mut self.jsonl {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn write_address_sheet(
+    workbook: &mut Workbook,
+    grouped: &std::collections::HashMap<String, Vec<&QueryResult>>,
+    addresses: &[String],
+    target_chains: &[String],
+    inactive_days: Option<i64>,
+    formats: &SheetFormats,
+) -> Result<()> {
+    let by_key: std::collections::HashMap<(&str, &str), &QueryResult> = grouped
+        .values()
+        .flatten()
+        .map(|r| ((r.address.as_str(), r.tx_chain.as_str()), *r))
+        .collect();
+    let extras = OptionalColumns::default().extras(ColumnData::new(grouped, inactive_days));
+    let offset = extras.len();
+    let cols_per_chain = if inactive_days.is_some() { 3 } else { 2 };
+
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.by_address"))?;
+    worksheet.write_string_with_format(0, 0, t("header.wallet_address"), &formats.header)?;
+    worksheet.set_column_width(0, 45)?;
+    write_column_headers(worksheet, 1, &extras, true, formats)?;
+    for (i, chain) in target_chains.iter().enumerate() {
+        let col = (i * cols_per_chain + 1 + offset) as u16;
+        let chain = chain_display_name(chain);
+        worksheet.write_string_with_format(0, col, tr!("header.chain_last_tx_time", chain), &formats.header)?;
+        worksheet.write_string_with_format(0, col + 1, format!("{} hash", chain), &formats.header)?;
+        worksheet.set_column_width(col, 20)?;
+        worksheet.set_column_width(col + 1, 70)?;
+        if inactive_days.is_some() {
+            worksheet.write_string_with_format(0, col + 2, tr!("header.chain_dormant", chain), &formats.header)?;
+            worksheet.set_column_width(col + 2, 14)?;
+        }
+    }
+    worksheet.set_freeze_panes(1, 1 + offset as u16)?;
+
+    let mut row_idx = 0;
+    for address in addresses {
+        let cells: Vec<(usize, &QueryResult)> = target_chains
+            .iter()
+            .enumerate()
+            .filter_map(|(j, chain)| by_key.get(&(address.as_str(), chain.as_str())).map(|row| (j, *row)))
+            .collect();
+        if cells.is_empty() {
+            continue;
+        }
+
+        row_idx += 1;
+        worksheet.write_string(row_idx, 0, address)?;
+        write_column_cells(worksheet, row_idx, 1, &extras, cells[0].1, inactive_days, &formats.plain)?;
+        for (j, row) in cells {
+            let col = (j * cols_per_chain + 1 + offset) as u16;
+            let cell_format = formats.for_status(row.status);
+            worksheet.write_string_with_format(row_idx, col, placeholders().time(row), cell_format)?;
+            write_hash_cell(worksheet, row_idx, col + 1, row, cell_format)?;
+            if let Some(dormant) = inactive_days.and_then(|days| is_dormant(row, days)) {
+                worksheet.write_boolean_with_format(row_idx, col + 2, dormant, cell_format)?;
+            }
+        }
+    }
+    worksheet.autofilter(0, 0, row_idx, (target_chains.len() * cols_per_chain + offset) as u16)?;
+    Ok(())
+}
+
+fn write_flat_sheet(
+    workbook: &mut Workbook,
+    grouped: &std::collections::HashMap<String, Vec<&QueryResult>>,
+    target_chains: &[String],
+    inactive_days: Option<i64>,
+    optional: &OptionalColumns,
+    formats: &SheetFormats,
+) -> Result<()> {
+    let columns = optional.sheet(&[Column::Chain, Column::Address], ColumnData::new(grouped, inactive_days));
+
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.all"))?;
+    write_column_headers(worksheet, 0, &columns, false, formats)?;
+    worksheet.set_freeze_panes(1, 0)?;
+
+    let mut row_idx = 0;
+    for chain in target_chains {
+        for row in grouped.get(chain).into_iter().flatten() {
+            row_idx += 1;
+            write_column_cells(worksheet, row_idx, 0, &columns, row, inactive_days, formats.for_status(row.status))?;
+        }
+    }
+    worksheet.autofilter(0, 0, row_idx, columns.len().saturating_sub(1) as u16)?;
+    Ok(())
+}
+
+fn write_summary_sheet(
+    workbook: &mut Workbook,
+    grouped: &std::collections::HashMap<String, Vec<&QueryResult>>,
+    target_chains: &[String],
+    address_count: usize,
+    elapsed: Duration,
+    latencies: &[u64],
+) -> Result<()> {
+    let worksheet = workbook.add_worksheet().set_name(t("sheet.summary"))?;
+    worksheet.set_column_width(0, 20)?;
+    worksheet.set_column_width(1, 15)?;
+
+    worksheet.write_string(0, 0, t("summary.total_addresses"))?;
+    worksheet.write_number(0, 1, address_count as f64)?;
+    worksheet.write_string(1, 0, t("summary.elapsed"))?;
+    worksheet.write_number(1, 1, (elapsed.as_secs_f64() * 10.0).round() / 10.0)?;
+
+    let mut row = 3;
+    worksheet.write_string(row, 0, t("header.chain"))?;
+    worksheet.write_string(row, 1, t("summary.active"))?;
+    worksheet.write_string(row, 2, t("status.no_transactions"))?;
+    worksheet.write_string(row, 3, t("summary.errors"))?;
+
+    let mut error_counts: std::collections::HashMap<QueryStatus, usize> = std::collections::HashMap::new();
+    for chain in target_chains {
+        let rows = grouped.get(chain).map(|rows| rows.as_slice()).unwrap_or_default();
+        let count = |status: QueryStatus| rows.iter().filter(|r| r.status == status).count();
+        for r in rows.iter().filter(|r| r.status.is_error()) {
+            *error_counts.entry(r.status).or_default() += 1;
+        }
+
+        row += 1;
+        worksheet.write_string(row, 0, chain_display_name(chain))?;
+        worksheet.write_number(row, 1, count(QueryStatus::Found) as f64)?;
+        worksheet.write_number(row, 2, count(QueryStatus::NoTransactions) as f64)?;
+        worksheet.write_number(row, 3, rows.iter().filter(|r| r.status.is_error()).count() as f64)?;
+    }
+
+    row += 2;
+    worksheet.write_string(row, 0, t("summary.error_type"))?;
+    worksheet.write_string(row, 1, t("summary.count"))?;
+    for status in QueryStatus::ERRORS {
+        row += 1;
+        worksheet.write_string(row, 0, status.label())?;
+        worksheet.write_number(row, 1, error_counts.get(&status).copied().unwrap_or_default() as f64)?;
+    }
+
+    if let Some((p50, p95)) = latency_percentiles(latencies) {
+        row += 2;
+        worksheet.write_string(row, 0, t("summary.latency_p50"))?;
+        worksheet.write_number(row, 1, p50 as f64)?;
+        worksheet.write_string(row + 1, 0, t("summary.latency_p95"))?;
+        worksheet.write_number(row + 1, 1, p95 as f64)?;
+    }
+
+    Ok(())
+}
+
+// a table header row names the address column; the errors sheet's count table has none, so its first row is matched on its own
+fn is_header_row(r: usize, row: &[calamine::Data]) -> bool {
+    let is = |cell: &calamine::Data, key| i18n::t_all(key).contains(&cell.to_string().as_str());
+    row.iter().any(|cell| is(cell, "header.address") || is(cell, "header.wallet_address")) || (r == 0 && row.first().is_some_and(|cell| is(cell, "header.error_type")))
+}
+
+// calamine only reads values, so the layout the writers set up is rebuilt from the headers: bold header rows,
+// status colours keyed off the status or hash column, frozen header, autofilter and fitted widths
+fn copy_existing_sheets(workbook: &mut Workbook, path: &Path, formats: &SheetFormats) -> Result<usize> {
+    let mut existing = calamine::open_workbook_auto(path).map_err(|e| anyhow::anyhow!(tr!("output.append_read_failed", path.display(), e)))?;
+    let names = existing.sheet_names();
+    for name in &names {
+        let range = existing.worksheet_range(name)?;
+        let worksheet = workbook.add_worksheet().set_name(name)?;
+        let (row0, col0) = range.start().unwrap_or_default();
+        let rows: Vec<&[calamine::Data]> = range.rows().collect();
+        let header_rows: Vec<usize> = rows.iter().enumerate().filter(|(r, row)| is_header_row(*r, row)).map(|(r, _)| r).collect();
+        let table = header_rows.last().copied();
+        let find_col = |labels: &[&str]| table.and_then(|h| rows[h].iter().position(|cell| labels.contains(&cell.to_string().as_str())));
+        let status_col = find_col(&i18n::t_all("header.status"));
+        let hash_col = find_col(&[i18n::t_all("header.tx_hash").as_slice(), &["Hash"]].concat());
+        let row_status = |row: &[calamine::Data]| match (status_col, hash_col) {
+            (Some(col), _) => row.get(col).and_then(|cell| QueryStatus::from_label(&cell.to_string())),
+            // rows without a transaction carry their status (or EMPTY_HASH_TEXT) in place of the hash
+            (None, Some(col)) => row.get(col).map(|cell| cell.to_string()).and_then(|hash| match QueryStatus::from_label(&hash) {
+                Some(status) => Some(status),
+                None if hash.is_empty() || placeholders().hash.as_deref() == Some(hash.as_str()) => None,
+                None => Some(QueryStatus::Found),
+            }),
+            (None, None) => None,
+        };
+
+        for (r, row) in rows.iter().enumerate() {
+            let format = if header_rows.contains(&r) {
+                &formats.header
+            } else {
+                match table {
+                    Some(h) if r > h => row_status(row).map_or(&formats.plain, |status| formats.for_status(status)),
+                    _ => &formats.plain,
+                }
+            };
+            for (c, cell) in row.iter().enumerate() {
+                let (r, c) = (row0 + r as u32, (col0 as usize + c) as u16);
+                match cell {
+                    calamine::Data::Empty => {}
+                    calamine::Data::Int(n) => {
+                        worksheet.write_number_with_format(r, c, *n as f64, format)?;
+                    }
+                    calamine::Data::Float(n) => {
+                        worksheet.write_number_with_format(r, c, *n, format)?;
+                    }
+                    calamine::Data::Bool(b) => {
+                        worksheet.write_boolean_with_format(r, c, *b, format)?;
+                    }
+                    other => {
+                        worksheet.write_string_with_format(r, c, other.to_string(), format)?;
+                    }
+                }
+            }
+        }
+
+        worksheet.autofit();
+        if let (Some(h), Some((end_row, end_col))) = (table, range.end()) {
+            let h = row0 + h as u32;
+            worksheet.set_freeze_panes(h + 1, 0)?;
+            worksheet.autofilter(h, col0 as u16, end_row.max(h), end_col as u16)?;
+        }
+    }
+    info!("{}", tr!("output.append_kept", path.display(), names.len()));
+    Ok(names.len())
+}
+
+fn date_new_sheets(workbook: &mut Workbook, kept: usize) -> Result<()> {
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    let mut taken: HashSet<String> = workbook.worksheets_mut().iter().take(kept).map(|ws| ws.name().to_lowercase()).collect();
+    for worksheet in workbook.worksheets_mut().iter_mut().skip(kept) {
+        let base = worksheet.name();
+        let name = (1..)
+            .map(|n| if n == 1 { format!("_{}", date) } else { format!("_{} ({})", date, n) })
+            .map(|suffix| {
+                let room = MAX_SHEET_NAME_CHARS.saturating_sub(suffix.chars().count());
+                format!("{}{}", base.chars().take(room).collect::<String>(), suffix)
+            })
+            .find(|name| !taken.contains(&name.to_lowercase()))
+            .unwrap_or_default();
+        taken.insert(name.to_lowercase());
+        worksheet.set_name(name)?;
+    }
+    Ok(())
+}
+
+fn mock_results(ctx: &QueryContext, fixtures: &MockFixtures, addresses: &[String], chains: &[String]) -> Vec<QueryResult> {
+    let results: Vec<QueryResult> = addresses
+        .iter()
+        .flat_map(|addr| chains.iter().filter(|chain| !ctx.is_completed(addr, chain)).map(|chain| fixtures.result(addr, chain)))
+        .collect();
+    ctx.record(&results);
+    ctx.keep(results)
+}
+
+async fn run_queries(
+    ctx: Arc<QueryContext>,
+    evm_addresses: &[String],
+    solana_addresses: &[String],
+    chains: &[String],
+    query_mode: &str,
+) -> Vec<QueryResult> {
+    let evm_chains: Vec<String> = chains.iter().filter(|c| *c != SOLANA_CHAIN).cloned().collect();
+    let query_evm = !evm_addresses.is_empty() && !evm_chains.is_empty();
+    let evm_only = matches!(query_mode, "tokens" | "custom");
+    let query_solana = evm_chains.len() < chains.len() && !solana_addresses.is_empty() && !evm_only;
+    if evm_only && ctx.secondary.is_some() {
+        warn!("{}", tr!("secondary.mode_skipped", query_mode));
+    }
+    if evm_only && evm_chains.len() < chains.len() && !solana_addresses.is_empty() {
+        warn!("{}", tr!("run.mode_skips_solana", query_mode, solana_addresses.len()));
+    }
+    match query_mode {
+        _ if !query_evm => {}
+        "single" => info!("{}", tr!("run.single_mode", evm_chains.len(), evm_addresses.len())),
+        "tokens" => info!("{}", tr!("run.tokens_mode", evm_chains.len(), evm_addresses.len())),
+        "custom" => info!("{}", tr!("run.custom_mode", evm_chains.len(), evm_addresses.len())),
+        _ => info!("{}", tr!("run.multi_mode", evm_chains.len(), evm_addresses.len())),
+    }
+    if query_solana {
+        info!("{}", tr!("run.solana", solana_addresses.len()));
+    }
+
+    let size = ctx.batch_size.max(1);
+    let groups = evm_addresses.len().max(solana_addresses.len()).div_ceil(size).max(1);
+    let group = |list: &[String], i: usize| -> Vec<String> { list.iter().skip(i * size).take(size).cloned().collect() };
+    let mut results = Vec::new();
+    for i in 0..groups {
+        if i > 0 && !ctx.batch_delay.is_zero() && !ctx.is_shutting_down() {
+            info!("{}", tr!("run.batch_pause", ctx.batch_delay.as_secs()));
+            tokio::time::sleep(ctx.batch_delay).await;
+        }
+        if ctx.is_shutting_down() {
+            break;
+        }
+        let (evm_group, solana_group) = (group(evm_addresses, i), group(solana_addresses, i));
+        if groups > 1 {
+            info!("{}", tr!("run.batch", i + 1, groups, evm_group.len() + solana_group.len()));
+        }
+        if query_evm && !evm_group.is_empty() {
+            results.extend(match (&ctx.fixtures, query_mode) {
+                (Some(fixtures), _) => mock_results(&ctx, fixtures, &evm_group, &evm_chains),
+                (None, "single") => get_last_txs_single_mode(ctx.clone(), &evm_group, &evm_chains, SingleMethod::Transactions).await,
+                (None, "tokens") => get_last_txs_single_mode(ctx.clone(), &evm_group, &evm_chains, SingleMethod::TokenTransfers).await,
+                (None, "custom") => get_last_txs_single_mode(ctx.clone(), &evm_group, &evm_chains, SingleMethod::Custom).await,
+                (None, _) => get_last_txs_batch(ctx.clone(), &evm_group, evm_chains.clone()).await,
+            });
+        }
+        if query_solana && !solana_group.is_empty() {
+            results.extend(match &ctx.fixtures {
+                Some(fixtures) => mock_results(&ctx, fixtures, &solana_group, &[SOLANA_CHAIN.to_string()]),
+                None => get_last_txs_solana(ctx.clone(), &solana_group).await,
+            });
+        }
+    }
+    results
+}
+
+pub struct Endpoints {
+    pub multichain: String,
+    pub solana: String,
+    pub chain_rpc: String,
+    pub etherscan: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            multichain: ANKR_RPC_BASE.to_string(),
+            solana: ANKR_SOLANA_RPC_BASE.to_string(),
+            chain_rpc: ANKR_CHAIN_RPC_BASE.to_string(),
+            etherscan: etherscan::ETHERSCAN_API.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FixtureEntry {
+    address: String,
+    #[serde(default)]
+    chain: Option<String>,
+    #[serde(default)]
+    status: Option<QueryStatus>,
+    #[serde(default)]
+    account_type: Option<AccountType>,
+    #[serde(default)]
+    transactions: Vec<Transaction>,
+}
+
+// canned results for PROVIDER=mock, keyed by lowercase address and normalized chain
+#[derive(Default)]
+pub struct MockFixtures {
+    results: std::collections::HashMap<(String, String), (Option<QueryStatus>, Vec<Transaction>)>,
+    account_types: std::collections::HashMap<String, AccountType>,
+}
+
+impl MockFixtures {
+    pub fn from_json(text: &str) -> Result<Self> {
+        let entries: Vec<FixtureEntry> = serde_json::from_str(text)?;
+        let mut fixtures = MockFixtures::default();
+        for entry in entries {
+            let address = entry.address.trim().to_lowercase();
+            if let Some(account_type) = entry.account_type {
+                fixtures.account_types.insert(address.clone(), account_type);
+            }
+            let Some(chain) = entry.chain.as_deref().map(normalize_chain) else {
+                continue;
+            };
+            let mut transactions = entry.transactions;
+            for tx in transactions.iter_mut().filter(|tx| tx.blockchain.is_empty()) {
+                tx.blockchain = chain.clone();
+            }
+            fixtures.results.insert((address, chain), (entry.status, transactions));
+        }
+        Ok(fixtures)
+    }
+
+    // a pair without an entry is an inactive wallet; the first listed transaction is the latest
+    fn result(&self, address: &str, chain: &str) -> QueryResult {
+        match self.results.get(&(address.to_lowercase(), chain.to_string())) {
+            Some((Some(status), _)) if *status != QueryStatus::Found => QueryResult::empty(address, chain, *status),
+            Some((_, transactions)) if !transactions.is_empty() => QueryResult::found(address, chain, &transactions[0]),
+            _ => QueryResult::empty(address, chain, QueryStatus::NoTransactions),
+        }
+    }
+
+    fn account_type(&self, address: &str) -> AccountType {
+        self.account_types.get(&address.to_lowercase()).copied().unwrap_or(AccountType::Eoa)
+    }
+
+    // the whole history as a single page, newest first like the real endpoint
+    fn history(&self, address: &str, chains: &[&str]) -> RpcResult {
+        let address = address.to_lowercase();
+        let mut transactions: Vec<Transaction> = chains
+            .iter()
+            .filter_map(|chain| self.results.get(&(address.clone(), chain.to_string())))
+            .flat_map(|(_, transactions)| transactions.iter().cloned())
+            .collect();
+        transactions.sort_by_key(|tx| std::cmp::Reverse(parse_timestamp(&tx.timestamp)));
+        RpcResult { next_page_token: None, transactions }
+    }
+}
+
+#[derive(Clone, Default)]
+pub enum Provider {
+    #[default]
+    Ankr,
+    Mock(Arc<MockFixtures>),
+}
+
+// QUERY_MODE=custom: any Ankr method, one request per address and chain
+#[derive(Clone, Debug)]
+pub struct CustomMethod {
+    pub method: String,
+    params: serde_json::Value,
+    // JSON Pointer into the result, e.g. /transactions/0/hash; empty for the whole result
+    pub result_path: String,
+}
+
+impl CustomMethod {
+    pub fn new(method: &str, params_template: &str, result_path: &str) -> Result<Self> {
+        let method = method.trim();
+        if method.is_empty() {
+            anyhow::bail!(t("config.custom_missing_method"));
+        }
+        let params = serde_json::from_str(params_template).map_err(|e| anyhow::anyhow!(tr!("config.custom_invalid_template", e)))?;
+        let result_path = result_path.trim();
+        if !result_path.is_empty() && !result_path.starts_with('/') {
+            anyhow::bail!(tr!("config.custom_invalid_path", result_path));
+        }
+        Ok(CustomMethod { method: method.to_string(), params, result_path: result_path.to_string() })
+    }
+
+    // placeholders are filled inside string values only, so an address can never break the JSON
+    pub fn params(&self, address: &str, chain: &str) -> serde_json::Value {
+        fn fill(value: &serde_json::Value, address: &str, chain: &str) -> serde_json::Value {
+            match value {
+                serde_json::Value::String(s) => serde_json::Value::String(s.replace("{address}", address).replace("{chain}", chain)),
+                serde_json::Value::Array(items) => items.iter().map(|item| fill(item, address, chain)).collect(),
+                serde_json::Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), fill(v, address, chain))).collect(),
+                other => other.clone(),
+            }
+        }
+        fill(&self.params, address, chain)
+    }
+
+    // None when the path is missing or null; strings are written as-is, anything else as compact JSON
+    pub fn extract(&self, result: &serde_json::Value) -> Option<String> {
+        match result.pointer(&self.result_path)? {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+}
+
+fn load_custom_method() -> Result<Option<CustomMethod>> {
+    if load_query_mode() != "custom" {
+        return Ok(None);
+    }
+    let var = |key: &str| std::env::var(key).unwrap_or_default();
+    let template = std::env::var("RPC_PARAMS_TEMPLATE").ok().filter(|v| !v.trim().is_empty());
+    let template = template.as_deref().unwrap_or(DEFAULT_RPC_PARAMS_TEMPLATE);
+    CustomMethod::new(&var("RPC_METHOD"), template, &var("RPC_RESULT_PATH")).map(Some)
+}
+
+impl Provider {
+    fn name(&self) -> &'static str {
+        match self {
+            Provider::Ankr => "ankr",
+            Provider::Mock(_) => "mock",
+        }
+    }
+}
+
+// SECONDARY_PROVIDER=etherscan: asked for every address and chain next to the primary provider
+#[derive(Clone, Debug)]
+pub struct SecondaryProvider {
+    pub api_key: String,
+    pub rate_limit_rps: Option<NonZeroU32>,
+}
+
+impl SecondaryProvider {
+    pub fn name(&self) -> &'static str {
+        "etherscan"
+    }
+}
+
+fn load_secondary_provider() -> Result<Option<SecondaryProvider>> {
+    match std::env::var("SECONDARY_PROVIDER").unwrap_or_default().trim().to_lowercase().as_str() {
+        "" | "none" => Ok(None),
+        "etherscan" => {
+            let api_key = std::env::var("ETHERSCAN_API_KEY").unwrap_or_default().trim().to_string();
+            if api_key.is_empty() {
+                anyhow::bail!(t("config.etherscan_missing_key"));
+            }
+            let rps = std::env::var("ETHERSCAN_RPS").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_ETHERSCAN_RPS);
+            Ok(Some(SecondaryProvider { api_key, rate_limit_rps: NonZeroU32::new(rps) }))
+        }
+        other => anyhow::bail!(tr!("config.invalid_secondary_provider", other)),
+    }
+}
+
+fn load_provider() -> Result<Provider> {
+    match std::env::var("PROVIDER").unwrap_or_default().trim().to_lowercase().as_str() {
+        "" | "ankr" => Ok(Provider::Ankr),
+        "mock" => {
+            let path = std::env::var("MOCK_FIXTURES").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+            let path = PathBuf::from(path.as_deref().unwrap_or(DEFAULT_FIXTURES_FILE));
+            let text = std::fs::read_to_string(&path).map_err(|e| anyhow::anyhow!(tr!("provider.fixtures_read_failed", path.display(), e)))?;
+            let fixtures = MockFixtures::from_json(&text).map_err(|e| anyhow::anyhow!(tr!("provider.fixtures_parse_failed", path.display(), e)))?;
+            info!("{}", tr!("provider.mock", path.display(), fixtures.results.len()));
+            Ok(Provider::Mock(Arc::new(fixtures)))
+        }
+        other => anyhow::bail!(tr!("config.invalid_provider", other)),
+    }
+}
+
+pub struct QueryConfig {
+    pub api_key: String,
+    pub endpoints: Endpoints,
+    pub provider: Provider,
+    pub chains: Vec<String>,
+    pub query_mode: String,
+    // required when query_mode is "custom"
+    pub custom_method: Option<CustomMethod>,
+    pub secondary: Option<SecondaryProvider>,
+    pub concurrency: usize,
+    pub adaptive_bounds: Option<(usize, usize)>,
+    pub chain_concurrency: std::collections::HashMap<String, usize>,
+    pub rate_limit_rps: Option<NonZeroU32>,
+    pub proxy_urls: Vec<String>,
+    pub client_settings: ClientSettings,
+    pub retry_delay: Duration,
+    pub skip_confirm: bool,
+    pub confirm_empty_delay: Option<Duration>,
+    pub query_span: bool,
+    pub batch_size: usize,
+    pub batch_delay: Duration,
+    pub save_failed: bool,
+    // provider-reported remaining requests below which concurrency is reduced
+    pub quota_low_remaining: u64,
+}
+
+// the built-in defaults with nothing read from the environment; from_env is what the CLI uses
+impl Default for QueryConfig {
+    fn default() -> Self {
+        QueryConfig {
+            api_key: String::new(),
+            endpoints: Endpoints::default(),
+            provider: Provider::default(),
+            chains: expand_target_chains(DEFAULT_CHAINS),
+            query_mode: DEFAULT_QUERY_MODE.to_string(),
+            custom_method: None,
+            secondary: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            adaptive_bounds: None,
+            chain_concurrency: std::collections::HashMap::new(),
+            rate_limit_rps: None,
+            proxy_urls: Vec::new(),
+            client_settings: ClientSettings::default(),
+            retry_delay: Duration::from_secs(DEFAULT_RETRY_DELAY_SECS),
+            skip_confirm: false,
+            confirm_empty_delay: None,
+            query_span: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            batch_delay: Duration::ZERO,
+            save_failed: false,
+            quota_low_remaining: DEFAULT_QUOTA_LOW_REMAINING,
+        }
+    }
+}
+
+impl QueryConfig {
+    pub fn from_env() -> Result<Self> {
+        let concurrency = parse_concurrency(std::env::var("CONCURRENCY").ok().as_deref())?;
+        Ok(QueryConfig {
+            api_key: std::env::var("ANKR_API_KEY").unwrap_or_else(|_| String::new()),
+            endpoints: Endpoints::default(),
+            provider: Provider::default(),
+            chains: load_target_chains(None),
+            query_mode: load_query_mode(),
+            custom_method: None,
+            secondary: None,
+            concurrency,
+            adaptive_bounds: load_adaptive_bounds(concurrency),
+            chain_concurrency: load_chain_concurrency(),
+            rate_limit_rps: load_rate_limit_rps(),
+            proxy_urls: load_proxy_urls(),
+            client_settings: load_client_settings(),
+            retry_delay: Duration::from_secs(DEFAULT_RETRY_DELAY_SECS),
+            skip_confirm: std::env::var("SKIP_CONFIRM").map(|v| v == "1").unwrap_or(false),
+            confirm_empty_delay: load_confirm_empty_delay(),
+            query_span: std::env::var("QUERY_SPAN").map(|v| v == "1").unwrap_or(false),
+            batch_size: std::env::var("BATCH_SIZE").ok().and_then(|v| v.trim().parse().ok()).filter(|&n| n > 0).unwrap_or(DEFAULT_BATCH_SIZE),
+            batch_delay: Duration::from_secs(std::env::var("BATCH_DELAY_SECS").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(0)),
+            save_failed: std::env::var("SAVE_FAILED").map(|v| v == "1").unwrap_or(false),
+            quota_low_remaining: load_quota_low_remaining(),
+        })
+    }
+}
+
+pub async fn query_addresses(config: &QueryConfig, addresses: &[String]) -> Result<Vec<QueryResult>> {
+    if config.query_mode == "custom" && config.custom_method.is_none() {
+        anyhow::bail!(t("config.custom_missing_method"));
+    }
+    let clients = ClientPool::new(&config.proxy_urls, &config.client_settings)?;
+    let ctx = Arc::new(QueryContext::new(clients, config, None));
+    let (solana_addresses, evm_addresses): (Vec<String>, Vec<String>) = addresses.iter().cloned().partition(|addr| is_solana_address(addr));
+    let (chains, _) = dedup_chains(config.chains.clone());
+    Ok(run_queries(ctx, &evm_addresses, &solana_addresses, &chains, &config.query_mode).await)
+}
+
+// nearest-rank percentiles of the successful request latencies
+fn latency_percentiles(latencies: &[u64]) -> Option<(u64, u64)> {
+    if latencies.is_empty() {
+        return None;
+    }
+    let mut sorted = latencies.to_vec();
+    sorted.sort_unstable();
+    let rank = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+    Some((rank(50), rank(95)))
+}
+
+fn print_run_summary(tally: &RunTally, address_count: usize, ctx: &QueryContext, elapsed: Duration) {
+    let errors: Vec<String> = QueryStatus::ERRORS.iter().map(|&status| format!("{} {}", status.label(), tally.count(status))).collect();
+
+    info!("{}", t("stats.header"));
+    info!("{}", tr!("stats.addresses", address_count, tally.active(), tally.inactive()));
+    info!("{}", tr!("stats.errors", errors.join(t("stats.separator"))));
+    info!("{}", tr!("stats.requests", ctx.requests.load(Ordering::Relaxed), ctx.throttled.load(Ordering::Relaxed)));
+    if let Some(quota) = *ctx.quota.lock().unwrap() {
+        let remaining = quota.remaining.map_or("?".to_string(), |r| r.to_string());
+        let remaining = quota.limit.map_or(remaining.clone(), |limit| format!("{}/{}", remaining, limit));
+        let reset = quota.reset_epoch.and_then(|epoch| Local.timestamp_opt(epoch, 0).single()).map_or("-".to_string(), |at| at.format("%Y-%m-%d %H:%M:%S").to_string());
+        info!("{}", tr!("stats.quota", remaining, reset));
+    }
+    if let Some((p50, p95)) = latency_percentiles(&ctx.latencies.lock().unwrap()) {
+        info!("{}", tr!("stats.latency", p50, p95));
+    }
+    info!("{}", tr!("stats.elapsed", format!("{:.1}", elapsed.as_secs_f64())));
+}
+
+fn run_report(tally: &RunTally, address_count: usize, ctx: &QueryContext, elapsed: Duration, outputs: Vec<String>, interrupted: bool) -> notify::RunReport {
+    let errors = QueryStatus::ERRORS.iter().map(|&status| (status.key().trim_start_matches("status.").to_string(), tally.count(status).into())).collect();
+    notify::RunReport {
+        interrupted,
+        addresses: address_count,
+        active: tally.active(),
+        inactive: tally.inactive(),
+        rows: tally.rows,
+        errors,
+        error_total: tally.errors(),
+        requests: ctx.requests.load(Ordering::Relaxed),
+        quota: *ctx.quota.lock().unwrap(),
+        outputs,
+        duration_secs: elapsed.as_secs_f64(),
+    }
+}
+
+fn write_run_manifest(manifest: &serde_json::Value) {
+    let written = File::create(RUN_MANIFEST_FILE).map_err(anyhow::Error::from).and_then(|file| Ok(serde_json::to_writer_pretty(io::BufWriter::new(file), manifest)?));
+    match written {
+        Ok(()) => info!("{}", tr!("manifest.saved", RUN_MANIFEST_FILE)),
+        Err(e) => warn!("{}", tr!("manifest.failed", RUN_MANIFEST_FILE, e)),
+    }
+}
+
+fn print_dry_run_plan(
+    addresses: &[String],
+    chains: &[String],
+    previous: &[QueryResult],
+    resumed: &[QueryResult],
+    query_mode: &str,
+    classify_accounts: bool,
+    confirm_empty: bool,
+) {
+    let done: HashSet<(&str, &str)> = previous.iter().chain(resumed).map(|r| (r.address.as_str(), r.tx_chain.as_str())).collect();
+    let (solana_addresses, evm_addresses): (Vec<&String>, Vec<&String>) = addresses.iter().partition(|addr| is_solana_address(addr));
+    let evm_chains: Vec<&String> = chains.iter().filter(|c| *c != SOLANA_CHAIN).collect();
+    let pending = |addr: &str, chain: &str| !done.contains(&(addr, chain));
+
+    info!("{}", t("dry_run.header"));
+    info!("{}", tr!("dry_run.addresses", addresses.len(), evm_addresses.len(), solana_addresses.len()));
+    info!("{}", tr!("dry_run.chains", chains.iter().map(|c| chain_display_name(c)).collect::<Vec<_>>().join(", ")));
+    if !done.is_empty() {
+        info!("{}", tr!("dry_run.skipped", done.len()));
+    }
+
+    let mut total = 0;
+    if !evm_chains.is_empty() && !evm_addresses.is_empty() {
+        if classify_accounts {
+            info!("{}", tr!("dry_run.classify", evm_addresses.len()));
+            total += evm_addresses.len();
+        }
+        if matches!(query_mode, "single" | "tokens" | "custom") {
+            let pairs = evm_addresses
+                .iter()
+                .flat_map(|addr| evm_chains.iter().filter(|chain| pending(addr, chain)))
+                .count();
+            info!("{}", tr!("dry_run.single", pairs));
+            if confirm_empty {
+                info!("{}", t("dry_run.confirm_empty"));
+            }
+            total += pairs;
+        } else {
+            let addrs = evm_addresses.iter().filter(|addr| evm_chains.iter().any(|chain| pending(addr, chain))).count();
+            let batch_size = load_rpc_batch_size();
+            let requests = if addrs > 1 { addrs.div_ceil(batch_size) } else { addrs };
+            info!("{}", tr!("dry_run.multi", addrs, requests, batch_size));
+            total += requests;
+        }
+    }
+    if let (Ok(Some(plan)), false, false) = (load_history_plan(), evm_chains.is_empty(), evm_addresses.is_empty()) {
+        info!("{}", tr!("dry_run.activity", evm_addresses.len(), evm_addresses.len(), evm_addresses.len() * plan.max_pages as usize));
+        total += evm_addresses.len();
+    }
+    if evm_chains.len() < chains.len() && !matches!(query_mode, "tokens" | "custom") {
+        let addrs = solana_addresses.iter().filter(|addr| pending(addr, SOLANA_CHAIN)).count();
+        info!("{}", tr!("dry_run.solana", addrs));
+        total += addrs;
+    }
+    info!("{}", tr!("dry_run.total", total));
+    info!("{}", tr!("dry_run.output", OUTPUT_FILE, CHECKPOINT_FILE));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+    #[test]
+    fn unset_or_invalid_concurrency_uses_the_default() {
+        assert_eq!(parse_concurrency(None).unwrap(), 10);
+        assert_eq!(parse_concurrency(Some(" ")).unwrap(), 10);
+        assert_eq!(parse_concurrency(Some("ten")).unwrap(), 10);
+    }
+
+    #[test]
+    fn zero_concurrency_is_rejected() {
+        assert!(parse_concurrency(Some("0")).is_err());
+    }
+
+    #[test]
+    fn large_concurrency_values_are_capped() {
+        assert_eq!(parse_concurrency(Some(" 25 ")).unwrap(), 25);
+        assert_eq!(parse_concurrency(Some("500")).unwrap(), 500);
+        assert_eq!(parse_concurrency(Some("10000")).unwrap(), 500);
+    }
+
+    #[test]
+    fn long_hashes_keep_the_first_twelve_chars() {
+        assert_eq!(short_hash("0xabababababababababababababababababababababababababababababababab"), "0xababababab");
+        assert_eq!(short_hash("0xabababab"), "0xabababab");
+    }
+
+    #[test]
+    fn short_and_empty_hashes_do_not_panic() {
+        assert_eq!(short_hash("0xab"), "0xab");
+        assert_eq!(short_hash(""), "");
+    }
+
+    #[test]
+    fn multibyte_input_is_cut_on_char_boundaries() {
+        assert_eq!(short_hash("哈希哈希哈希哈希哈希哈希哈希"), "哈希哈希哈希哈希哈希哈希");
+    }
+
+    #[test]
+    fn all_expands_to_every_supported_chain() {
+        let chains = expand_target_chains("all");
+        assert!(chains.len() > 6);
+        assert_eq!(&chains[..3], ["eth", "bsc", "polygon"]);
+        assert!(chains.iter().any(|chain| chain == "solana"));
+    }
+
+    #[test]
+    fn exclusions_remove_chains_by_alias() {
+        let chains = expand_target_chains("ALL, -bnb,-sol");
+        assert!(!chains.iter().any(|chain| chain == "bsc" || chain == "solana"));
+        assert_eq!(chains.len(), expand_target_chains("all").len() - 2);
+
+        assert_eq!(expand_target_chains("eth,bsc,polygon,-bsc"), ["eth", "polygon"]);
+    }
+
+    #[test]
+    fn explicit_lists_are_normalized() {
+        assert_eq!(expand_target_chains("Ethereum, matic,,"), ["eth", "polygon"]);
+    }
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs.iter().map(|(name, value)| (HeaderName::from_static(name), HeaderValue::from_static(value))).collect()
+    }
+
+    #[test]
+    fn no_quota_headers() {
+        assert_eq!(parse_rate_limit_headers(&headers(&[("content-type", "application/json")]), NOW), None);
+    }
+
+    #[test]
+    fn reset_in_seconds_from_now() {
+        let parsed = parse_rate_limit_headers(&headers(&[("x-ratelimit-remaining", "42"), ("x-ratelimit-limit", "1000"), ("x-ratelimit-reset", "30")]), NOW);
+        assert_eq!(parsed, Some(RateLimitHeaders { remaining: Some(42), limit: Some(1000), reset_epoch: Some(NOW + 30) }));
+    }
+
+    #[test]
+    fn reset_as_unix_timestamp_in_seconds_or_milliseconds() {
+        let parsed = parse_rate_limit_headers(&headers(&[("ratelimit-remaining", "7"), ("ratelimit-reset", "1700000600")]), NOW).unwrap();
+        assert_eq!(parsed.reset_epoch, Some(1_700_000_600));
+
+        let parsed = parse_rate_limit_headers(&headers(&[("x-rate-limit-reset", "1700000600000")]), NOW).unwrap();
+        assert_eq!(parsed, RateLimitHeaders { remaining: None, limit: None, reset_epoch: Some(1_700_000_600) });
+    }
+
+    #[test]
+    fn list_values_use_the_first_number() {
+        let parsed = parse_rate_limit_headers(&headers(&[("ratelimit-remaining", "5, 100;w=60"), ("x-ratelimit-reset-requests", "12s")]), NOW).unwrap();
+        assert_eq!(parsed.remaining, Some(5));
+        assert_eq!(parsed.reset_epoch, Some(NOW + 12));
+    }
+}
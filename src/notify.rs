@@ -0,0 +1,156 @@
+// completion notifications for unattended runs (WEBHOOK_URL, TELEGRAM_BOT_TOKEN)
+
+use crate::i18n::{t, tr};
+use crate::REQUEST_TIMEOUT_SECS;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use tokio::time::{timeout, Duration};
+use tracing::{info, warn};
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+// the Bot API refuses uploads above 50 MB and captions above 1024 characters
+const TELEGRAM_MAX_UPLOAD_BYTES: u64 = 50 * 1024 * 1024;
+const TELEGRAM_MAX_CAPTION_CHARS: usize = 1024;
+const TELEGRAM_UPLOAD_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WebhookFormat {
+    Json,
+    Slack,
+    Discord,
+}
+
+pub(crate) struct Webhook {
+    url: String,
+    format: WebhookFormat,
+}
+
+pub(crate) fn load_webhook() -> Option<Webhook> {
+    let url = std::env::var("WEBHOOK_URL").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())?;
+    let format = match std::env::var("WEBHOOK_FORMAT").unwrap_or_default().trim().to_lowercase().as_str() {
+        "slack" => WebhookFormat::Slack,
+        "discord" => WebhookFormat::Discord,
+        _ => WebhookFormat::Json,
+    };
+    Some(Webhook { url, format })
+}
+
+#[derive(Serialize)]
+pub(crate) struct RunReport {
+    pub(crate) interrupted: bool,
+    pub(crate) addresses: usize,
+    pub(crate) active: usize,
+    pub(crate) inactive: usize,
+    pub(crate) rows: usize,
+    // error status (no_data, parse_error, ...) -> rows
+    pub(crate) errors: serde_json::Map<String, Value>,
+    pub(crate) error_total: usize,
+    pub(crate) requests: usize,
+    // lowest remaining quota reported by the provider, when it sends rate limit headers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) quota: Option<crate::RateLimitHeaders>,
+    pub(crate) outputs: Vec<String>,
+    pub(crate) duration_secs: f64,
+}
+
+impl RunReport {
+    fn message(&self) -> String {
+        let key = if self.interrupted { "notify.interrupted" } else { "notify.completed" };
+        tr!(key, self.addresses, self.active, self.inactive, self.error_total, format!("{:.1}", self.duration_secs), self.outputs.join(", "))
+    }
+}
+
+fn payload(format: WebhookFormat, report: &RunReport) -> Value {
+    match format {
+        WebhookFormat::Json => json!({ "event": if report.interrupted { "run_interrupted" } else { "run_completed" }, "summary": report }),
+        WebhookFormat::Slack => json!({ "text": report.message() }),
+        WebhookFormat::Discord => json!({ "content": report.message() }),
+    }
+}
+
+// a failed notification only warns: the results are already on disk
+pub(crate) async fn send_webhook(client: &Client, webhook: &Webhook, report: &RunReport) {
+    let request = client.post(&webhook.url).json(&payload(webhook.format, report)).send();
+    let error = match timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), request).await {
+        Ok(Ok(response)) if response.status().is_success() => {
+            info!("{}", t("notify.sent"));
+            return;
+        }
+        Ok(Ok(response)) => format!("HTTP {}", response.status().as_u16()),
+        Ok(Err(e)) => e.without_url().to_string(),
+        Err(_) => tr!("error.timeout", REQUEST_TIMEOUT_SECS),
+    };
+    warn!("{}", tr!("notify.failed", error));
+}
+
+pub(crate) struct Telegram {
+    token: String,
+    chat_id: String,
+}
+
+pub(crate) fn load_telegram() -> Option<Telegram> {
+    let read = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    match (read("TELEGRAM_BOT_TOKEN"), read("TELEGRAM_CHAT_ID")) {
+        (Some(token), Some(chat_id)) => Some(Telegram { token, chat_id }),
+        (None, None) => None,
+        _ => {
+            warn!("{}", t("notify.telegram_incomplete"));
+            None
+        }
+    }
+}
+
+// reqwest errors carry the request URL, which holds the bot token
+async fn telegram_call(request: RequestBuilder, limit: Duration) -> Result<(), String> {
+    let response = match timeout(limit, request.send()).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => return Err(e.without_url().to_string()),
+        Err(_) => return Err(tr!("error.timeout", limit.as_secs())),
+    };
+    let body: Value = response.json().await.map_err(|e| e.without_url().to_string())?;
+    match body["ok"].as_bool() {
+        Some(true) => Ok(()),
+        _ => Err(body["description"].as_str().unwrap_or("unknown error").to_string()),
+    }
+}
+
+async fn send_document(client: &Client, telegram: &Telegram, path: &Path, caption: String) -> Result<(), String> {
+    let bytes = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let form = Form::new()
+        .text("chat_id", telegram.chat_id.clone())
+        .text("caption", caption)
+        .part("document", Part::bytes(bytes).file_name(name));
+    let url = format!("{}/bot{}/sendDocument", TELEGRAM_API, telegram.token);
+    telegram_call(client.post(url).multipart(form), Duration::from_secs(TELEGRAM_UPLOAD_TIMEOUT_SECS)).await
+}
+
+async fn send_message(client: &Client, telegram: &Telegram, text: String) -> Result<(), String> {
+    let url = format!("{}/bot{}/sendMessage", TELEGRAM_API, telegram.token);
+    let body = json!({ "chat_id": telegram.chat_id, "text": text });
+    telegram_call(client.post(url).json(&body), Duration::from_secs(REQUEST_TIMEOUT_SECS)).await
+}
+
+// uploads the xlsx with the summary as its caption, or sends the summary alone when there is no file or it is too big
+pub(crate) async fn send_telegram(client: &Client, telegram: &Telegram, report: &RunReport, document: Option<&Path>) {
+    let message = report.message();
+    let document = document.filter(|path| match std::fs::metadata(path) {
+        Ok(meta) if meta.len() > TELEGRAM_MAX_UPLOAD_BYTES => {
+            warn!("{}", tr!("notify.telegram_too_large", path.display(), meta.len() / (1024 * 1024), TELEGRAM_MAX_UPLOAD_BYTES / (1024 * 1024)));
+            false
+        }
+        Ok(_) => true,
+        Err(_) => false,
+    });
+    let result = match document {
+        Some(path) => send_document(client, telegram, path, message.chars().take(TELEGRAM_MAX_CAPTION_CHARS).collect()).await,
+        None => send_message(client, telegram, message).await,
+    };
+    match result {
+        Ok(()) => info!("{}", t("notify.telegram_sent")),
+        Err(e) => warn!("{}", tr!("notify.telegram_failed", e)),
+    }
+}
@@ -0,0 +1,56 @@
+use evm_tx_checker::{identify_input, normalize_address, private_key_to_address};
+use proptest::prelude::*;
+use sha3::{Digest, Keccak256};
+
+fn is_checksummed(address: &str) -> bool {
+    let Some(hex) = address.strip_prefix("0x") else {
+        return false;
+    };
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    let hash = Keccak256::digest(hex.to_lowercase().as_bytes());
+    hex.chars().enumerate().all(|(i, c)| {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        !c.is_ascii_alphabetic() || c.is_ascii_uppercase() == (nibble >= 8)
+    })
+}
+
+proptest! {
+    #[test]
+    fn random_addresses_normalize_to_checksummed(bytes in any::<[u8; 20]>(), prefixed in any::<bool>()) {
+        let hex = hex::encode(bytes);
+        let input = if prefixed { format!("0x{}", hex) } else { hex.clone() };
+
+        let (normalized, is_private_key) = identify_input(&input);
+        prop_assert!(!is_private_key);
+
+        let address = normalize_address(normalized).unwrap();
+        prop_assert_eq!(address.len(), 42);
+        prop_assert!(is_checksummed(&address), "not checksummed: {}", address);
+        prop_assert_eq!(address.to_lowercase(), format!("0x{}", hex));
+    }
+
+    #[test]
+    fn normalization_is_idempotent(bytes in any::<[u8; 20]>()) {
+        let address = normalize_address(&hex::encode(bytes)).unwrap();
+        prop_assert_eq!(normalize_address(&address), Some(address.clone()));
+        prop_assert_eq!(normalize_address(&address.to_uppercase().replacen("0X", "0x", 1)), Some(address));
+    }
+}
+
+#[test]
+fn private_keys_derive_known_addresses() {
+    let vectors = [
+        ("0x0000000000000000000000000000000000000000000000000000000000000001", "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"),
+        ("0000000000000000000000000000000000000000000000000000000000000002", "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF"),
+        ("0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318", "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"),
+    ];
+    for (key, expected) in vectors {
+        let (normalized, is_private_key) = identify_input(key);
+        assert!(is_private_key);
+        let address = private_key_to_address(normalized).unwrap();
+        assert_eq!(address, expected);
+        assert!(is_checksummed(&address));
+    }
+}
@@ -0,0 +1,47 @@
+use evm_tx_checker::load_wallet_addresses;
+use std::path::PathBuf;
+
+const ADDRESS: &str = "0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5";
+
+fn write_csv(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("evm_tx_checker_csv_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn load(name: &str, contents: &str) -> Vec<(String, Option<String>)> {
+    let wallets = load_wallet_addresses(Some(&write_csv(name, contents)), &[]).unwrap();
+    wallets.into_iter().map(|w| (w.address, w.label)).collect()
+}
+
+#[test]
+fn comma() {
+    let rows = load("comma.csv", &format!("address,label\n{},main\n", ADDRESS));
+    assert_eq!(rows, vec![(ADDRESS.to_string(), Some("main".to_string()))]);
+}
+
+#[test]
+fn semicolon() {
+    let rows = load("semicolon.csv", &format!("address;label\n{};main\n", ADDRESS));
+    assert_eq!(rows, vec![(ADDRESS.to_string(), Some("main".to_string()))]);
+}
+
+#[test]
+fn tab() {
+    let rows = load("tab.csv", &format!("\u{feff}address\tlabel\n{}\tmain\n", ADDRESS));
+    assert_eq!(rows, vec![(ADDRESS.to_string(), Some("main".to_string()))]);
+}
+
+#[test]
+fn quoted_commas_do_not_outvote_the_real_delimiter() {
+    let rows = load("quoted.csv", &format!("address;label\n{};\"a, b, c\"\n", ADDRESS));
+    assert_eq!(rows, vec![(ADDRESS.to_string(), Some("a, b, c".to_string()))]);
+}
+
+#[test]
+fn single_column() {
+    let rows = load("single.csv", &format!("address\n{}\n", ADDRESS));
+    assert_eq!(rows, vec![(ADDRESS.to_string(), None)]);
+}
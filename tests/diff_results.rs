@@ -0,0 +1,68 @@
+use evm_tx_checker::{diff_results, Change, QueryResult, QueryStatus};
+
+const WALLET: &str = "0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5";
+const OTHER: &str = "0x1111111111111111111111111111111111111111";
+
+fn row(address: &str, chain: &str, status: QueryStatus, hash: &str) -> QueryResult {
+    QueryResult { tx_hash: hash.to_string(), tx_time: "2023-09-12 06:06".to_string(), ..QueryResult::empty(address, chain, status) }
+}
+
+fn changes(previous: &[QueryResult], current: &[QueryResult]) -> Vec<(String, String, Change)> {
+    diff_results(previous, current).into_iter().map(|c| (c.current.address.clone(), c.current.tx_chain.clone(), c.change)).collect()
+}
+
+#[test]
+fn unchanged_rows_are_not_reported() {
+    let rows = [row(WALLET, "eth", QueryStatus::Found, "0xaa"), row(OTHER, "eth", QueryStatus::NoTransactions, "")];
+    assert!(changes(&rows, &rows).is_empty());
+}
+
+#[test]
+fn transitions() {
+    let previous = [
+        row(WALLET, "eth", QueryStatus::NoTransactions, ""),
+        row(WALLET, "bsc", QueryStatus::Found, "0xaa"),
+        row(OTHER, "eth", QueryStatus::Found, "0xbb"),
+    ];
+    let current = [
+        row(WALLET, "eth", QueryStatus::Found, "0xcc"),
+        row(WALLET, "bsc", QueryStatus::Found, "0xdd"),
+        row(OTHER, "eth", QueryStatus::NoTransactions, ""),
+    ];
+    assert_eq!(
+        changes(&previous, &current),
+        vec![
+            (WALLET.to_string(), "eth".to_string(), Change::Activated),
+            (WALLET.to_string(), "bsc".to_string(), Change::NewTransaction),
+            (OTHER.to_string(), "eth".to_string(), Change::Deactivated),
+        ]
+    );
+}
+
+#[test]
+fn rows_match_by_address_ignoring_case() {
+    let previous = [row(&WALLET.to_lowercase(), "eth", QueryStatus::Found, "0xAA")];
+    let current = [row(WALLET, "eth", QueryStatus::Found, "0xaa")];
+    assert!(changes(&previous, &current).is_empty());
+}
+
+#[test]
+fn new_addresses_on_known_chains() {
+    let previous = [row(WALLET, "eth", QueryStatus::Found, "0xaa")];
+    let current = [row(WALLET, "eth", QueryStatus::Found, "0xaa"), row(OTHER, "eth", QueryStatus::NoTransactions, "")];
+    assert_eq!(changes(&previous, &current), vec![(OTHER.to_string(), "eth".to_string(), Change::NewAddress)]);
+}
+
+#[test]
+fn chains_in_only_one_run_are_skipped() {
+    let previous = [row(WALLET, "eth", QueryStatus::Found, "0xaa"), row(WALLET, "polygon", QueryStatus::Found, "0xbb")];
+    let current = [row(WALLET, "eth", QueryStatus::Found, "0xaa"), row(WALLET, "bsc", QueryStatus::Found, "0xcc")];
+    assert!(changes(&previous, &current).is_empty());
+}
+
+#[test]
+fn failed_rows_are_skipped() {
+    let previous = [row(WALLET, "eth", QueryStatus::Timeout, "超时"), row(OTHER, "eth", QueryStatus::Found, "0xaa")];
+    let current = [row(WALLET, "eth", QueryStatus::Found, "0xcc"), row(OTHER, "eth", QueryStatus::NetworkError, "网络错误")];
+    assert!(changes(&previous, &current).is_empty());
+}
@@ -0,0 +1,46 @@
+use evm_tx_checker::{Direction, QueryResult, QueryStatus};
+
+const WALLET: &str = "0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5";
+const OTHER: &str = "0x1111111111111111111111111111111111111111";
+
+fn last_tx(from: Option<&str>, to: Option<&str>) -> QueryResult {
+    QueryResult {
+        tx_hash: format!("0x{}", "ab".repeat(32)),
+        tx_time: "2023-09-12 06:06".to_string(),
+        tx_epoch: Some(0x65000000),
+        tx_from: from.map(str::to_string),
+        tx_to: to.map(str::to_string),
+        ..QueryResult::empty(WALLET, "eth", QueryStatus::Found)
+    }
+}
+
+#[test]
+fn outgoing_ignores_case() {
+    assert_eq!(last_tx(Some(&WALLET.to_lowercase()), Some(OTHER)).direction(), Some(Direction::Out));
+}
+
+#[test]
+fn incoming() {
+    assert_eq!(last_tx(Some(OTHER), Some(&WALLET.to_uppercase().replace("0X", "0x"))).direction(), Some(Direction::In));
+}
+
+#[test]
+fn self_transfer() {
+    assert_eq!(last_tx(Some(WALLET), Some(&WALLET.to_lowercase())).direction(), Some(Direction::SelfTransfer));
+}
+
+#[test]
+fn contract_creation_has_no_recipient() {
+    assert_eq!(last_tx(Some(WALLET), None).direction(), Some(Direction::ContractCreation));
+    assert_eq!(last_tx(Some(WALLET), Some("")).direction(), Some(Direction::ContractCreation));
+}
+
+#[test]
+fn unrelated_sender_and_recipient() {
+    assert_eq!(last_tx(Some(OTHER), Some(OTHER)).direction(), Some(Direction::Other));
+}
+
+#[test]
+fn unknown_without_sender() {
+    assert_eq!(last_tx(None, Some(OTHER)).direction(), None);
+}
@@ -0,0 +1,29 @@
+use evm_tx_checker::format_ether_value;
+
+#[test]
+fn one_ether() {
+    assert_eq!(format_ether_value("0xde0b6b3a7640000").as_deref(), Some("1"));
+}
+
+#[test]
+fn fractional() {
+    assert_eq!(format_ether_value("0x3782dace9d90000").as_deref(), Some("0.25"));
+    assert_eq!(format_ether_value("0x1").as_deref(), Some("0.000000000000000001"));
+}
+
+#[test]
+fn zero() {
+    assert_eq!(format_ether_value("0x0").as_deref(), Some("0"));
+    assert_eq!(format_ether_value("0x").as_deref(), Some("0"));
+}
+
+#[test]
+fn large() {
+    // 1,000,000 ether
+    assert_eq!(format_ether_value("0xd3c21bcecceda1000000").as_deref(), Some("1000000"));
+}
+
+#[test]
+fn garbage() {
+    assert_eq!(format_ether_value("not hex"), None);
+}
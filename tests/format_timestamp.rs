@@ -0,0 +1,37 @@
+use chrono::{Local, TimeZone};
+use evm_tx_checker::{format_timestamp, parse_timestamp};
+
+fn local(ts: i64) -> String {
+    Local.timestamp_opt(ts, 0).unwrap().format("%Y-%m-%d %H:%M").to_string()
+}
+
+#[test]
+fn prefixed_hex() {
+    assert_eq!(parse_timestamp("0x65000000"), Some(0x65000000));
+    assert_eq!(format_timestamp("0x65000000"), local(0x65000000));
+}
+
+#[test]
+fn bare_hex() {
+    assert_eq!(format_timestamp("65000000"), local(0x65000000));
+}
+
+#[test]
+fn decimal_is_read_as_hex() {
+    // 1700000000 is not a hex literal, but it only contains hex digits, so it parses as 0x1700000000
+    assert_eq!(parse_timestamp("1700000000"), Some(0x1700000000));
+    assert_eq!(format_timestamp("1700000000"), local(0x1700000000));
+}
+
+#[test]
+fn out_of_range() {
+    assert_eq!(format_timestamp("0x7fffffffffffffff"), "时间格式错误");
+}
+
+#[test]
+fn garbage() {
+    assert_eq!(parse_timestamp("not a timestamp"), None);
+    assert_eq!(format_timestamp("not a timestamp"), "时间解析失败");
+    assert_eq!(format_timestamp("0x"), "时间解析失败");
+    assert_eq!(format_timestamp(""), "时间解析失败");
+}
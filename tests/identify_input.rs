@@ -0,0 +1,92 @@
+use evm_tx_checker::{address_problem, identify_input};
+
+const ADDRESS: &str = "0x742d35cc6634c0532925a3b844bc9e7595f8feb5";
+const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+#[test]
+fn prefixed_address() {
+    assert_eq!(identify_input(ADDRESS), (ADDRESS, false));
+}
+
+#[test]
+fn bare_address() {
+    assert_eq!(identify_input(&ADDRESS[2..]), (&ADDRESS[2..], false));
+}
+
+#[test]
+fn prefixed_private_key() {
+    assert_eq!(identify_input(PRIVATE_KEY), (PRIVATE_KEY, true));
+}
+
+#[test]
+fn bare_private_key() {
+    assert_eq!(identify_input(&PRIVATE_KEY[2..]), (&PRIVATE_KEY[2..], true));
+}
+
+#[test]
+fn mixed_case_is_kept_as_is() {
+    let address = "0x742d35Cc6634C0532925a3b844Bc9e7595f8fEb5";
+    assert_eq!(identify_input(address), (address, false));
+
+    let key = "4C0883A69102937D6231471B5DBB6204FE5129617082792AE468D01A3F362318";
+    assert_eq!(identify_input(key), (key, true));
+}
+
+#[test]
+fn surrounding_whitespace_is_trimmed() {
+    assert_eq!(identify_input(&format!("  {}\t\r\n", ADDRESS)), (ADDRESS, false));
+    assert_eq!(identify_input(&format!(" {} ", PRIVATE_KEY)), (PRIVATE_KEY, true));
+}
+
+#[test]
+fn embedded_whitespace_falls_through_as_address() {
+    let input = "0x742d35cc6634c053 2925a3b844bc9e7595f8feb5";
+    assert_eq!(identify_input(input), (input, false));
+}
+
+#[test]
+fn too_short_falls_through_as_address() {
+    assert_eq!(identify_input("0x742d35cc"), ("0x742d35cc", false));
+    assert_eq!(identify_input(&ADDRESS[..41]), (&ADDRESS[..41], false));
+}
+
+#[test]
+fn non_hex_falls_through_as_address() {
+    let address = "0x742d35cc6634c0532925a3b844bc9e7595f8fzzz";
+    assert_eq!(identify_input(address), (address, false));
+
+    let key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f36231g";
+    assert_eq!(identify_input(key), (key, false));
+}
+
+#[test]
+fn uppercase_prefix_is_not_recognised() {
+    let input = "0X742d35cc6634c0532925a3b844bc9e7595f8feb5";
+    assert_eq!(identify_input(input), (input, false));
+}
+
+#[test]
+fn empty_input() {
+    assert_eq!(identify_input(""), ("", false));
+    assert_eq!(identify_input("   "), ("", false));
+}
+
+#[test]
+fn near_miss_addresses_are_reported_as_malformed() {
+    assert!(address_problem(&ADDRESS[..41]).is_some());
+    assert!(address_problem(&format!("{}a", ADDRESS)).is_some());
+    assert!(address_problem(&ADDRESS[3..]).is_some());
+
+    let problem = address_problem("0x742d35cc6634c0532925a3b844bc9e7595f8fzzz").unwrap();
+    assert!(problem.contains("40"), "{}", problem);
+    assert!(address_problem("0x742d35cc6634c053 2925a3b844bc9e7595f8feb5").is_some());
+}
+
+#[test]
+fn valid_and_unrelated_inputs_are_not_malformed_addresses() {
+    assert_eq!(address_problem(ADDRESS), None);
+    assert_eq!(address_problem(&ADDRESS[2..]), None);
+    assert_eq!(address_problem(PRIVATE_KEY), None);
+    assert_eq!(address_problem("0x742d35cc"), None);
+    assert_eq!(address_problem("DRpbCBMxVnDK7maPM5tGv6MvB3v1sRMC86PZ8okm21hy"), None);
+}
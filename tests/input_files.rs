@@ -0,0 +1,61 @@
+use evm_tx_checker::load_wallet_addresses;
+use std::path::{Path, PathBuf};
+
+const FIRST: &str = "0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5";
+const SECOND: &str = "0x1234567890AbcdEF1234567890aBcdef12345678";
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("evm_tx_checker_inputs_{}_{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn load(spec: &str) -> Vec<String> {
+    load_wallet_addresses(Some(Path::new(spec)), &[]).unwrap().into_iter().map(|w| w.address).collect()
+}
+
+#[test]
+fn comma_separated_files_are_concatenated_and_deduplicated() {
+    let dir = dir("list");
+    std::fs::write(dir.join("a.csv"), format!("address\n{}\n", FIRST)).unwrap();
+    std::fs::write(dir.join("b.txt"), format!("{}\n{}\n", FIRST.to_lowercase(), SECOND)).unwrap();
+
+    let spec = format!("{},{}", dir.join("a.csv").display(), dir.join("b.txt").display());
+    assert_eq!(load(&spec), vec![FIRST.to_string(), SECOND.to_string()]);
+}
+
+#[test]
+fn glob_matches_are_read_in_sorted_order() {
+    let dir = dir("glob");
+    std::fs::write(dir.join("2.txt"), format!("{}\n", FIRST)).unwrap();
+    std::fs::write(dir.join("1.txt"), format!("{}\n", SECOND)).unwrap();
+
+    assert_eq!(load(&dir.join("*.txt").display().to_string()), vec![SECOND.to_string(), FIRST.to_string()]);
+}
+
+#[test]
+fn glob_without_matches_is_an_error() {
+    let dir = dir("empty");
+    assert!(load_wallet_addresses(Some(&dir.join("*.csv")), &[]).is_err());
+}
+
+#[test]
+fn xlsx_reads_address_and_label_columns_and_skips_summary_sheet() {
+    let dir = dir("xlsx");
+    let path = dir.join("wallets.xlsx");
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let summary = workbook.add_worksheet().set_name("汇总").unwrap();
+    summary.write_string(0, 0, SECOND).unwrap();
+    let sheet = workbook.add_worksheet().set_name("全部").unwrap();
+    sheet.write_string(0, 0, "状态").unwrap();
+    sheet.write_string(0, 1, "钱包地址").unwrap();
+    sheet.write_string(0, 2, "备注").unwrap();
+    sheet.write_string(1, 1, format!("  {} ", FIRST)).unwrap();
+    sheet.write_string(1, 2, "hot").unwrap();
+    workbook.save(&path).unwrap();
+
+    let wallets = load_wallet_addresses(Some(&path), &[]).unwrap();
+    assert_eq!(wallets.len(), 1);
+    assert_eq!(wallets[0].address, FIRST);
+    assert_eq!(wallets[0].label.as_deref(), Some("hot"));
+}
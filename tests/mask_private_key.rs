@@ -0,0 +1,42 @@
+use evm_tx_checker::Masking;
+
+const KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+#[test]
+fn default_shows_four_characters_on_each_side() {
+    assert_eq!(Masking::default().mask(KEY), "0x4c08...2318");
+    assert_eq!(Masking::default().mask(&KEY[2..]), "4c08...2318");
+}
+
+#[test]
+fn boundary_lengths_are_fully_masked() {
+    let masking = Masking::default();
+    assert_eq!(masking.mask("abcdefghij"), "***");
+    assert_eq!(masking.mask("abcdefghijk"), "***");
+    assert_eq!(masking.mask("0xabcdefghij"), "***");
+    assert_eq!(masking.mask("abcdefghijklmnop"), "abcd...mnop");
+}
+
+#[test]
+fn very_short_inputs_do_not_panic() {
+    let masking = Masking { prefix: 10, suffix: 10 };
+    for value in ["", "0x", "a", "0xab"] {
+        assert_eq!(masking.mask(value), "***");
+    }
+}
+
+#[test]
+fn multibyte_characters_do_not_panic() {
+    assert_eq!(Masking { prefix: 2, suffix: 2 }.mask("钱包地址写错了一行"), "钱包...一行");
+}
+
+#[test]
+fn zero_length_shows_nothing() {
+    assert_eq!(Masking { prefix: 0, suffix: 0 }.mask(KEY), "***");
+}
+
+#[test]
+fn custom_lengths() {
+    assert_eq!(Masking { prefix: 6, suffix: 0 }.mask(KEY), "0x4c0883...");
+    assert_eq!(Masking { prefix: 0, suffix: 2 }.mask(KEY), "0x...18");
+}
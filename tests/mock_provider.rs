@@ -0,0 +1,67 @@
+use evm_tx_checker::{query_addresses, MockFixtures, Provider, QueryConfig, QueryResult, QueryStatus};
+use std::sync::Arc;
+
+const ACTIVE: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f8fEb5";
+const FLAKY: &str = "0x1111111111111111111111111111111111111111";
+const SOLANA: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+const FIXTURES: &str = r#"[
+    {"address": "0x742D35CC6634C0532925A3B844BC9E7595F8FEB5", "chain": "Ethereum", "transactions": [
+        {"hash": "0xaaaa", "timestamp": "0x65000000", "from": "0x742d35cc6634c0532925a3b844bc9e7595f8feb5", "to": "0x2222", "value": "0xde0b6b3a7640000"},
+        {"hash": "0xbbbb", "timestamp": "0x64000000"}
+    ]},
+    {"address": "0x1111111111111111111111111111111111111111", "chain": "bsc", "status": "Timeout"},
+    {"address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "chain": "solana", "transactions": [
+        {"hash": "5sig", "timestamp": "0x65000000"}
+    ]}
+]"#;
+
+async fn query(query_mode: &str, addresses: &[&str]) -> Vec<QueryResult> {
+    let config = QueryConfig {
+        provider: Provider::Mock(Arc::new(MockFixtures::from_json(FIXTURES).unwrap())),
+        chains: vec!["eth".to_string(), "bsc".to_string(), "solana".to_string()],
+        query_mode: query_mode.to_string(),
+        ..QueryConfig::from_env().unwrap()
+    };
+    let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+    query_addresses(&config, &addresses).await.unwrap()
+}
+
+fn find<'a>(results: &'a [QueryResult], address: &str, chain: &str) -> &'a QueryResult {
+    results.iter().find(|r| r.address == address && r.tx_chain == chain).unwrap()
+}
+
+#[tokio::test]
+async fn first_fixture_transaction_is_the_latest() {
+    let results = query("single", &[ACTIVE]).await;
+    assert_eq!(results.len(), 2);
+    let eth = find(&results, ACTIVE, "eth");
+    assert_eq!(eth.status, QueryStatus::Found);
+    assert_eq!(eth.tx_hash, "0xaaaa");
+    assert_eq!(eth.tx_epoch, Some(0x65000000));
+    assert_eq!(eth.tx_to.as_deref(), Some("0x2222"));
+    assert_eq!(eth.tx_value.as_deref(), Some("1"));
+}
+
+#[tokio::test]
+async fn missing_pairs_have_no_transactions_and_statuses_are_replayed() {
+    let results = query("multi", &[ACTIVE, FLAKY]).await;
+    assert_eq!(results.len(), 4);
+    assert_eq!(find(&results, ACTIVE, "bsc").status, QueryStatus::NoTransactions);
+    assert_eq!(find(&results, FLAKY, "eth").status, QueryStatus::NoTransactions);
+    assert_eq!(find(&results, FLAKY, "bsc").status, QueryStatus::Timeout);
+}
+
+#[tokio::test]
+async fn solana_addresses_use_the_solana_fixture() {
+    let results = query("multi", &[SOLANA]).await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].tx_chain, "solana");
+    assert_eq!(results[0].tx_hash, "5sig");
+}
+
+#[test]
+fn invalid_fixtures_are_rejected() {
+    assert!(MockFixtures::from_json(r#"{"address": "0x1"}"#).is_err());
+    assert!(MockFixtures::from_json(r#"[{"chain": "eth"}]"#).is_err());
+}
@@ -0,0 +1,54 @@
+use evm_tx_checker::{load_previous_results, Placeholders, QueryResult, QueryStatus, NO_TX_TIME};
+
+fn row(status: QueryStatus, hash: &str, time: &str) -> QueryResult {
+    QueryResult {
+        tx_hash: hash.to_string(),
+        tx_time: time.to_string(),
+        ..QueryResult::empty("0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5", "eth", status)
+    }
+}
+
+#[test]
+fn defaults_keep_the_status_label_and_na() {
+    let placeholders = Placeholders::default();
+    let empty = row(QueryStatus::NoTransactions, "无交易", NO_TX_TIME);
+    assert_eq!(placeholders.hash(&empty), "无交易");
+    assert_eq!(placeholders.time(&empty), "N/A");
+}
+
+#[test]
+fn overrides_replace_rows_without_a_transaction() {
+    let placeholders = Placeholders { hash: Some(String::new()), time: Some("none".to_string()) };
+    for status in [QueryStatus::NoTransactions, QueryStatus::NetworkError, QueryStatus::Timeout] {
+        let empty = row(status, "网络错误", NO_TX_TIME);
+        assert_eq!(placeholders.hash(&empty), "");
+        assert_eq!(placeholders.time(&empty), "none");
+    }
+}
+
+#[test]
+fn found_rows_are_untouched() {
+    let placeholders = Placeholders { hash: Some(String::new()), time: Some(String::new()) };
+    let found = row(QueryStatus::Found, "0xabab", "2023-09-12 06:06");
+    assert_eq!(placeholders.hash(&found), "0xabab");
+    assert_eq!(placeholders.time(&found), "2023-09-12 06:06");
+}
+
+#[test]
+fn previous_csv_status_column_wins_over_a_custom_hash() {
+    let path = std::env::temp_dir().join(format!("placeholders_{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "Chain,Address,Time,Hash,Status\n\
+         eth,0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5,,,No transactions\n\
+         bsc,0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5,,,Network error\n\
+         polygon,0x742D35Cc6634C0532925A3B844bc9e7595f8Feb5,2023-09-12 06:06,0xabab,Found\n",
+    )
+    .unwrap();
+    let results = load_previous_results(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let statuses: Vec<QueryStatus> = results.iter().map(|r| r.status).collect();
+    assert_eq!(statuses, [QueryStatus::NoTransactions, QueryStatus::NetworkError, QueryStatus::Found]);
+    assert_eq!(results[2].tx_hash, "0xabab");
+}
@@ -0,0 +1,603 @@
+use evm_tx_checker::{query_addresses, CustomMethod, Endpoints, QueryConfig, QueryResult, QueryStatus, SecondaryProvider};
+use serde_json::{json, Value};
+use std::time::Duration;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f8fEb5";
+const TX_HASH: &str = "0xabababababababababababababababababababababababababababababababab";
+
+fn config(server: &MockServer) -> QueryConfig {
+    QueryConfig {
+        endpoints: Endpoints {
+            multichain: server.uri(),
+            solana: format!("{}/solana", server.uri()),
+            chain_rpc: server.uri(),
+            etherscan: format!("{}/etherscan", server.uri()),
+        },
+        chains: vec!["eth".to_string()],
+        query_mode: "single".to_string(),
+        concurrency: 1,
+        retry_delay: Duration::ZERO,
+        quota_low_remaining: 0,
+        ..QueryConfig::default()
+    }
+}
+
+fn reply(transactions: Value) -> impl Fn(&Request) -> ResponseTemplate {
+    move |request: &Request| {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": body["id"],
+            "result": { "transactions": transactions },
+        }))
+    }
+}
+
+fn with_tx() -> impl Fn(&Request) -> ResponseTemplate {
+    reply(json!([{ "hash": TX_HASH, "timestamp": "0x65000000", "blockchain": "eth" }]))
+}
+
+async fn query(server: &MockServer) -> QueryResult {
+    let mut results = query_addresses(&config(server), &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.len(), 1);
+    results.remove(0)
+}
+
+#[tokio::test]
+async fn transaction_found() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+    assert_eq!(result.tx_hash, TX_HASH);
+    assert_eq!(result.tx_chain, "eth");
+    assert_eq!(result.tx_epoch, Some(0x65000000));
+}
+
+async fn query_confirming_empty(server: &MockServer) -> QueryResult {
+    let mut config = config(server);
+    config.confirm_empty_delay = Some(Duration::ZERO);
+    let mut results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.len(), 1);
+    results.remove(0)
+}
+
+#[tokio::test]
+async fn empty_result_is_reported_immediately_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).expect(1).mount(&server).await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::NoTransactions);
+}
+
+#[tokio::test]
+async fn empty_result_is_rechecked_before_reporting_no_transactions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).expect(2).mount(&server).await;
+
+    let result = query_confirming_empty(&server).await;
+    assert_eq!(result.status, QueryStatus::NoTransactions);
+    assert_eq!(result.tx_epoch, None);
+}
+
+#[tokio::test]
+async fn recheck_can_find_a_transaction() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).up_to_n_times(1).mount(&server).await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let result = query_confirming_empty(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+    assert_eq!(result.tx_hash, TX_HASH);
+}
+
+#[tokio::test]
+async fn recheck_gets_its_own_parse_retry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).up_to_n_times(1).mount(&server).await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{not json"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let result = query_confirming_empty(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+}
+
+#[tokio::test]
+async fn malformed_json_is_retried_once_then_reported_as_parse_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{not json"))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::ParseError);
+    assert!(result.error.is_some());
+}
+
+#[tokio::test]
+async fn truncated_body_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"jsonrpc": "2.0", "id": 1, "result": {"transa"#))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_like_rate_limits() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+        .up_to_n_times(3)
+        .expect(3)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+}
+
+#[tokio::test]
+async fn rate_limited_request_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("Too Many Requests"))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+}
+
+#[tokio::test]
+async fn persistent_rate_limit_ends_as_network_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("Too Many Requests"))
+        .expect(5)
+        .mount(&server)
+        .await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::NetworkError);
+}
+
+#[tokio::test]
+async fn mismatched_response_id_is_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 999_999, "result": { "transactions": [] } })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let result = query(&server).await;
+    assert_eq!(result.status, QueryStatus::ParseError);
+}
+
+#[tokio::test]
+async fn multichain_confirms_each_missing_chain() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).expect(3).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_mode = "multi".to_string();
+    config.chains = vec!["eth".to_string(), "bsc".to_string()];
+    let results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.status == QueryStatus::NoTransactions));
+}
+
+#[tokio::test]
+async fn throttled_confirmation_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).up_to_n_times(1).mount(&server).await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(429)).up_to_n_times(1).expect(1).mount(&server).await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_mode = "multi".to_string();
+    let results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, QueryStatus::Found);
+}
+
+#[tokio::test]
+async fn multichain_skip_confirm_sends_a_single_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).expect(1).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_mode = "multi".to_string();
+    config.chains = vec!["eth".to_string(), "bsc".to_string()];
+    config.skip_confirm = true;
+    let results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.status == QueryStatus::NoTransactions));
+}
+
+fn bare(extra: Value) -> impl Fn(&Request) -> ResponseTemplate {
+    move |request: &Request| {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let mut response = json!({ "jsonrpc": "2.0", "id": body["id"] });
+        response.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        ResponseTemplate::new(200).set_body_json(response)
+    }
+}
+
+async fn query_multichain(server: &MockServer) -> Vec<QueryResult> {
+    let mut config = config(server);
+    config.query_mode = "multi".to_string();
+    config.chains = vec!["eth".to_string(), "bsc".to_string()];
+    config.skip_confirm = true;
+    query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap()
+}
+
+#[tokio::test]
+async fn multichain_missing_result_is_retried_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(bare(json!({}))).up_to_n_times(1).expect(1).mount(&server).await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+
+    let results = query_multichain(&server).await;
+    assert_eq!(results.iter().find(|r| r.tx_chain == "eth").unwrap().status, QueryStatus::Found);
+    assert_eq!(results.iter().find(|r| r.tx_chain == "bsc").unwrap().status, QueryStatus::NoTransactions);
+}
+
+#[tokio::test]
+async fn multichain_persistently_missing_result_is_no_data() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(bare(json!({ "result": null }))).expect(2).mount(&server).await;
+
+    let results = query_multichain(&server).await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.status == QueryStatus::NoData && r.error.is_none()));
+}
+
+#[tokio::test]
+async fn multichain_rpc_error_is_no_data_without_retry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(bare(json!({ "error": { "code": -32000, "message": "upstream unavailable" } })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = query_multichain(&server).await;
+    assert!(results.iter().all(|r| r.status == QueryStatus::NoData));
+    assert!(results.iter().all(|r| r.error.as_deref() == Some("upstream unavailable")));
+}
+
+// newest first unless the request asks for ascending order
+fn ordered() -> impl Fn(&Request) -> ResponseTemplate {
+    move |request: &Request| {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let timestamp = if body["params"]["descOrder"] == json!(false) { "0x5f5e1000" } else { "0x65000000" };
+        ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": body["id"],
+            "result": { "transactions": [{ "hash": TX_HASH, "timestamp": timestamp, "blockchain": "eth" }] },
+        }))
+    }
+}
+
+#[tokio::test]
+async fn query_span_fetches_the_first_transaction() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ordered()).expect(2).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_span = true;
+    let results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results[0].first_tx_epoch, Some(0x5f5e1000));
+    assert_eq!(results[0].span_days(), Some((0x65000000 - 0x5f5e1000) / 86_400));
+}
+
+#[tokio::test]
+async fn query_span_in_multichain_mode_only_queries_found_chains() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ordered()).expect(2).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_mode = "multi".to_string();
+    config.chains = vec!["eth".to_string(), "bsc".to_string()];
+    config.skip_confirm = true;
+    config.query_span = true;
+    let results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.iter().find(|r| r.tx_chain == "eth").unwrap().first_tx_epoch, Some(0x5f5e1000));
+    assert_eq!(results.iter().find(|r| r.tx_chain == "bsc").unwrap().first_tx_epoch, None);
+}
+
+#[tokio::test]
+async fn query_span_is_off_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ordered()).expect(1).mount(&server).await;
+
+    assert_eq!(query(&server).await.first_tx_epoch, None);
+}
+
+#[tokio::test]
+async fn tokens_mode_reports_the_newest_transfer() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(body["method"], "ankr_getTokenTransfers");
+            ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": body["id"],
+                "result": { "transfers": [{
+                    "transactionHash": TX_HASH,
+                    "timestamp": 0x65000000,
+                    "fromAddress": ADDRESS,
+                    "toAddress": "0x1111111111111111111111111111111111111111",
+                    "contractAddress": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                    "value": "12.5",
+                    "tokenSymbol": "USDT",
+                    "blockchain": "eth",
+                }] },
+            }))
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut config = config(&server);
+    config.query_mode = "tokens".to_string();
+    let result = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap().remove(0);
+    assert_eq!(result.status, QueryStatus::Found);
+    assert_eq!(result.tx_hash, TX_HASH);
+    assert_eq!(result.tx_epoch, Some(0x65000000));
+    assert_eq!(result.tx_value.as_deref(), Some("12.5"));
+    assert_eq!(result.token_symbol.as_deref(), Some("USDT"));
+    assert_eq!(result.token_contract.as_deref(), Some("0xdac17f958d2ee523a2206206994597c13d831ec7"));
+}
+
+#[tokio::test]
+async fn tokens_mode_without_transfers_is_no_transactions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(bare(json!({ "result": { "transfers": [] } }))).expect(1).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_mode = "tokens".to_string();
+    let result = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap().remove(0);
+    assert_eq!(result.status, QueryStatus::NoTransactions);
+}
+
+#[tokio::test]
+async fn custom_method_fills_the_template_and_extracts_the_path() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(body["method"], "ankr_getAccountBalance");
+            assert_eq!(body["params"], json!({ "blockchain": ["eth"], "walletAddress": ADDRESS, "onlyWhitelisted": true }));
+            ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": body["id"],
+                "result": { "totalBalanceUsd": "42.5", "assets": [] },
+            }))
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut config = config(&server);
+    config.query_mode = "custom".to_string();
+    let template = r#"{"blockchain": ["{chain}"], "walletAddress": "{address}", "onlyWhitelisted": true}"#;
+    config.custom_method = Some(CustomMethod::new("ankr_getAccountBalance", template, "/totalBalanceUsd").unwrap());
+    let result = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap().remove(0);
+    assert_eq!(result.status, QueryStatus::Found);
+    assert_eq!(result.rpc_value.as_deref(), Some("42.5"));
+}
+
+#[tokio::test]
+async fn custom_method_missing_path_is_no_transactions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(bare(json!({ "result": { "assets": [] } }))).expect(1).mount(&server).await;
+
+    let mut config = config(&server);
+    config.query_mode = "custom".to_string();
+    config.custom_method = Some(CustomMethod::new("ankr_getAccountBalance", "{}", "/assets/0").unwrap());
+    let result = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap().remove(0);
+    assert_eq!(result.status, QueryStatus::NoTransactions);
+    assert_eq!(result.rpc_value, None);
+}
+
+#[test]
+fn custom_method_rejects_invalid_settings() {
+    assert!(CustomMethod::new("ankr_getAccountBalance", "{\"address\": ", "").is_err());
+    assert!(CustomMethod::new("", "{}", "").is_err());
+    assert!(CustomMethod::new("ankr_getAccountBalance", "{}", "totalBalanceUsd").is_err());
+    let custom = CustomMethod::new("ankr_getAccountBalance", "{}", "").unwrap();
+    assert_eq!(custom.extract(&json!({ "a": 1 })).as_deref(), Some(r#"{"a":1}"#));
+}
+
+const OTHER: &str = "0x1111111111111111111111111111111111111111";
+
+// each transaction hash is the queried address, so a misrouted response is easy to spot
+fn tx_for(request: &Value) -> Value {
+    let address = request["params"]["address"].as_str().unwrap();
+    json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "transactions": [{ "hash": address, "timestamp": "0x65000000", "blockchain": "eth" }] } })
+}
+
+async fn query_batch(server: &MockServer) -> Vec<QueryResult> {
+    let mut config = config(server);
+    config.query_mode = "multi".to_string();
+    config.skip_confirm = true;
+    query_addresses(&config, &[ADDRESS.to_string(), OTHER.to_string()]).await.unwrap()
+}
+
+#[tokio::test]
+async fn batch_responses_are_routed_by_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let body: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            let mut responses: Vec<Value> = body.iter().rev().map(tx_for).collect();
+            responses.push(json!({ "jsonrpc": "2.0", "id": 999_999, "result": { "transactions": [] } }));
+            ResponseTemplate::new(200).set_body_json(responses)
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = query_batch(&server).await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.status == QueryStatus::Found && r.tx_hash == r.address));
+}
+
+#[tokio::test]
+async fn throttled_batch_is_retried_whole() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(429)).up_to_n_times(2).expect(2).mount(&server).await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let body: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200).set_body_json(body.iter().map(tx_for).collect::<Vec<_>>())
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = query_batch(&server).await;
+    assert!(results.iter().all(|r| r.status == QueryStatus::Found));
+}
+
+#[tokio::test]
+async fn address_missing_from_batch_is_queried_on_its_own() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            match body.as_array() {
+                Some(batch) => ResponseTemplate::new(200).set_body_json(vec![tx_for(&batch[1])]),
+                None => ResponseTemplate::new(200).set_body_json(tx_for(&body)),
+            }
+        })
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let results = query_batch(&server).await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.status == QueryStatus::Found && r.tx_hash == r.address));
+}
+
+const ETHERSCAN_HASH: &str = "0xcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd";
+
+fn etherscan(transactions: Value) -> ResponseTemplate {
+    let (status, message) = if transactions.as_array().is_some_and(|t| t.is_empty()) { ("0", "No transactions found") } else { ("1", "OK") };
+    ResponseTemplate::new(200).set_body_json(json!({ "status": status, "message": message, "result": transactions }))
+}
+
+fn etherscan_tx(timestamp: u64) -> Value {
+    json!([{ "hash": ETHERSCAN_HASH, "timeStamp": timestamp.to_string(), "from": ADDRESS, "to": "", "value": "1000" }])
+}
+
+async fn query_both(server: &MockServer) -> QueryResult {
+    let mut config = config(server);
+    config.secondary = Some(SecondaryProvider { api_key: "key".to_string(), rate_limit_rps: None });
+    let mut results = query_addresses(&config, &[ADDRESS.to_string()]).await.unwrap();
+    assert_eq!(results.len(), 1);
+    results.remove(0)
+}
+
+#[tokio::test]
+async fn newer_secondary_transaction_wins() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+    Mock::given(method("GET")).respond_with(etherscan(etherscan_tx(0x66000000))).expect(1).mount(&server).await;
+
+    let result = query_both(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+    assert_eq!(result.tx_hash, ETHERSCAN_HASH);
+    assert_eq!(result.tx_epoch, Some(0x66000000));
+    assert_eq!(result.provider.as_deref(), Some("etherscan"));
+}
+
+#[tokio::test]
+async fn newer_primary_transaction_is_kept() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+    Mock::given(method("GET")).respond_with(etherscan(etherscan_tx(0x64000000))).expect(1).mount(&server).await;
+
+    let result = query_both(&server).await;
+    assert_eq!(result.tx_hash, TX_HASH);
+    assert_eq!(result.provider.as_deref(), Some("ankr"));
+}
+
+#[tokio::test]
+async fn secondary_fills_in_when_primary_has_no_transactions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).expect(1).mount(&server).await;
+    Mock::given(method("GET")).respond_with(etherscan(etherscan_tx(0x64000000))).expect(1).mount(&server).await;
+
+    let result = query_both(&server).await;
+    assert_eq!(result.status, QueryStatus::Found);
+    assert_eq!(result.tx_hash, ETHERSCAN_HASH);
+    assert_eq!(result.provider.as_deref(), Some("etherscan"));
+}
+
+#[tokio::test]
+async fn both_providers_empty_is_no_transactions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(reply(json!([]))).expect(1).mount(&server).await;
+    Mock::given(method("GET")).respond_with(etherscan(json!([]))).expect(1).mount(&server).await;
+
+    let result = query_both(&server).await;
+    assert_eq!(result.status, QueryStatus::NoTransactions);
+    assert_eq!(result.provider.as_deref(), Some("ankr"));
+}
+
+#[tokio::test]
+async fn invalid_etherscan_key_is_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = query_both(&server).await;
+    assert_eq!(result.tx_hash, TX_HASH);
+    assert_eq!(result.provider.as_deref(), Some("ankr"));
+}
+
+#[tokio::test]
+async fn etherscan_rate_limit_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(with_tx()).expect(1).mount(&server).await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "0", "message": "NOTOK", "result": "Max rate limit reached" })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET")).respond_with(etherscan(etherscan_tx(0x66000000))).expect(1).mount(&server).await;
+
+    let result = query_both(&server).await;
+    assert_eq!(result.tx_hash, ETHERSCAN_HASH);
+}
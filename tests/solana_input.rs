@@ -0,0 +1,15 @@
+use evm_tx_checker::load_wallet_addresses;
+
+const EVM: &str = "0x742D35CC6634C1532925a3B844bC9e7595f8FEB5";
+const SOLANA: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+#[test]
+fn bare_hex_addresses_stay_evm_when_solana_is_targeted() {
+    let path = std::env::temp_dir().join(format!("evm_tx_checker_solana_{}.txt", std::process::id()));
+    // no 0 digit, so the bare form is also valid base58
+    std::fs::write(&path, format!("742d35cc6634c1532925a3b844bc9e7595f8feb5\n{}\n{}\n", EVM, SOLANA)).unwrap();
+
+    let addresses: Vec<String> = load_wallet_addresses(Some(&path), &["eth".to_string(), "solana".to_string()]).unwrap().into_iter().map(|w| w.address).collect();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(addresses, vec![EVM.to_string(), SOLANA.to_string()]);
+}